@@ -0,0 +1,326 @@
+// llm.rs - 모델 백엔드 추상화
+//
+// 에이전트는 LlmBackend 트레이트만 알고, 실제 모델(Claude)이나
+// 미리 준비한 응답을 재생하는 스크립트 백엔드는 여기서 구현합니다.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use claude_sdk_rs::{Config, StreamFormat};
+use claude_sdk_rs::runtime::process::execute_claude;
+use serde::{Serialize, Deserialize};
+use crate::agent::prompt::{SYSTEM_PROMPT, tools_prompt};
+use crate::error::{AppError, Result};
+
+pub use crate::tools::ToolSpec;
+
+const CLAUDE_TIMEOUT_SECS: u64 = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+    // 어시스턴트 메시지가 요청한 도구 호출
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+impl ChatMessage {
+    pub fn user(content: impl Into<String>) -> Self {
+        ChatMessage {
+            role: Role::User,
+            content: content.into(),
+            tool_calls: Vec::new(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>, tool_calls: Vec<ToolCall>) -> Self {
+        ChatMessage {
+            role: Role::Assistant,
+            content: content.into(),
+            tool_calls,
+        }
+    }
+}
+
+/// 모델이 요청한 도구 호출 한 건
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+/// 모델 응답 한 건. tool_calls가 비어 있으면 최종 응답
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LlmResponse {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+#[async_trait]
+pub trait LlmBackend: Send + Sync {
+    /// 지금까지의 대화와 사용 가능한 도구 목록을 보내고 다음 응답을 받음
+    async fn send(&self, messages: &[ChatMessage], tools: &[ToolSpec]) -> Result<LlmResponse>;
+}
+
+// 테스트 등에서 백엔드를 공유할 수 있도록 Arc도 백엔드로 취급
+#[async_trait]
+impl<T: LlmBackend + ?Sized> LlmBackend for Arc<T> {
+    async fn send(&self, messages: &[ChatMessage], tools: &[ToolSpec]) -> Result<LlmResponse> {
+        (**self).send(messages, tools).await
+    }
+}
+
+/// Claude Code CLI를 사용하는 백엔드
+pub struct ClaudeBackend {
+    // Client::send는 `;`, `<`, `|` 같은 문자가 들어간 질의를 거부해서 C 코드를 보낼 수 없으므로
+    // 설정만 들고 있다가 execute_claude를 직접 호출함
+    config: Config,
+}
+
+impl Default for ClaudeBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClaudeBackend {
+    pub fn new() -> Self {
+        ClaudeBackend {
+            config: Config {
+                stream_format: StreamFormat::Text,
+                timeout_secs: Some(CLAUDE_TIMEOUT_SECS),
+                ..Config::default()
+            },
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.config.model = Some(model.into());
+        self
+    }
+}
+
+#[async_trait]
+impl LlmBackend for ClaudeBackend {
+    async fn send(&self, messages: &[ChatMessage], tools: &[ToolSpec]) -> Result<LlmResponse> {
+        let mut config = self.config.clone();
+        config.system_prompt = Some(format!("{}\n{}", SYSTEM_PROMPT, tools_prompt(tools)));
+
+        // CLI 호출은 대화 상태를 유지하지 않으므로 매번 전체 대화를 보냄
+        let output = execute_claude(&config, &render_transcript(messages)).await?;
+        parse_response(output.trim())
+    }
+}
+
+/// 스크립트 백엔드가 돌려줄 응답 한 건
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScriptedReply {
+    /// Claude 응답처럼 ```tool 블록이 들어 있는 원문. parse_response로 변환
+    Raw(String),
+    Response(LlmResponse),
+}
+
+impl From<LlmResponse> for ScriptedReply {
+    fn from(response: LlmResponse) -> Self {
+        ScriptedReply::Response(response)
+    }
+}
+
+/// 파일이나 벡터에 준비된 응답을 순서대로 돌려주는 백엔드
+///
+/// 네트워크 없이 에이전트 테스트를 돌리기 위한 용도입니다.
+/// 응답 파일은 `LlmResponse` 객체나 응답 원문 문자열의 JSON 배열입니다.
+pub struct ScriptedBackend {
+    responses: Mutex<VecDeque<ScriptedReply>>,
+    requests: Mutex<Vec<Vec<ChatMessage>>>,
+}
+
+impl ScriptedBackend {
+    pub fn new(responses: Vec<LlmResponse>) -> Self {
+        Self::from_replies(responses.into_iter().map(ScriptedReply::from).collect())
+    }
+
+    pub fn from_replies(replies: Vec<ScriptedReply>) -> Self {
+        ScriptedBackend {
+            responses: Mutex::new(replies.into()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let replies: Vec<ScriptedReply> = serde_json::from_str(&content)?;
+        Ok(Self::from_replies(replies))
+    }
+
+    /// 지금까지 받은 요청들 (테스트에서 검사용)
+    pub fn requests(&self) -> Vec<Vec<ChatMessage>> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl LlmBackend for ScriptedBackend {
+    async fn send(&self, messages: &[ChatMessage], _tools: &[ToolSpec]) -> Result<LlmResponse> {
+        self.requests.lock().unwrap().push(messages.to_vec());
+        let reply = self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| AppError::LlmError("Scripted responses exhausted".to_string()))?;
+        match reply {
+            ScriptedReply::Raw(text) => parse_response(text.trim()),
+            ScriptedReply::Response(response) => Ok(response),
+        }
+    }
+}
+
+/// 대화 기록을 한 개의 프롬프트 문자열로 만듦
+pub fn render_transcript(messages: &[ChatMessage]) -> String {
+    messages
+        .iter()
+        .map(|m| {
+            let role = match m.role {
+                Role::User => "사용자",
+                Role::Assistant => "어시스턴트",
+            };
+            let mut text = format!("[{}]\n{}", role, m.content);
+            for call in &m.tool_calls {
+                text.push_str(&format!("\n```tool\n{}\n```", serde_json::to_string(call).unwrap_or_default()));
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Claude 텍스트 응답에서 ```tool 블록을 떼어내 LlmResponse로 변환
+pub fn parse_response(reply: &str) -> Result<LlmResponse> {
+    const OPEN: &str = "```tool";
+    const CLOSE: &str = "```";
+
+    // 깨진 도구 블록은 원문과 함께 돌려줘서 에이전트가 모델에게 다시 요청할 수 있게 함
+    let invalid = |message: String| AppError::ToolCallParseError {
+        reply: reply.to_string(),
+        message,
+    };
+
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    let mut rest = reply;
+    while let Some(start) = rest.find(OPEN) {
+        text.push_str(&rest[..start]);
+        let body = &rest[start + OPEN.len()..];
+        let end = body.find(CLOSE).ok_or_else(|| invalid("Unterminated tool block".to_string()))?;
+        let call: ToolCall = serde_json::from_str(body[..end].trim())
+            .map_err(|e| invalid(format!("Invalid tool call JSON: {}", e)))?;
+        tool_calls.push(call);
+        rest = &body[end + CLOSE.len()..];
+    }
+    text.push_str(rest);
+
+    Ok(LlmResponse {
+        text: text.trim().to_string(),
+        tool_calls,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_tool_calls() {
+        let reply = r#"먼저 파일을 봅니다.
+```tool
+{"name": "read_file", "arguments": {"path": "src/var.c"}}
+```
+그리고 정의도 찾습니다.
+```tool
+{"name": "find_var_definitions", "arguments": {"filename": "src/var.c", "line": 362, "varname": "p"}}
+```"#;
+        let response = parse_response(reply).unwrap();
+        assert_eq!(response.tool_calls.len(), 2);
+        assert_eq!(response.tool_calls[0].name, "read_file");
+        assert_eq!(response.tool_calls[1].arguments["line"], 362);
+        assert!(response.text.contains("정의도 찾습니다"));
+        assert!(!response.text.contains("```"));
+    }
+
+    #[test]
+    fn test_parse_response_final_report() {
+        let response = parse_response("## 보고서\n취약점 없음").unwrap();
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.text, "## 보고서\n취약점 없음");
+    }
+
+    #[test]
+    fn test_parse_response_invalid_tool_block() {
+        assert!(matches!(
+            parse_response("```tool\n{not json}\n```"),
+            Err(AppError::ToolCallParseError { .. })
+        ));
+        match parse_response("```tool\n{\"name\": \"read_file\"}") {
+            Err(AppError::ToolCallParseError { reply, message }) => {
+                assert!(reply.starts_with("```tool"));
+                assert_eq!(message, "Unterminated tool block");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_render_transcript_round_trip() {
+        let call = ToolCall {
+            name: "read_file".to_string(),
+            arguments: serde_json::json!({"path": "a.c"}),
+        };
+        let messages = vec![
+            ChatMessage::user("분석해줘"),
+            ChatMessage::assistant("파일을 읽습니다.", vec![call.clone()]),
+        ];
+        let transcript = render_transcript(&messages);
+        assert!(transcript.starts_with("[사용자]\n분석해줘"));
+        // 렌더링된 도구 블록은 다시 파싱할 수 있어야 함
+        let reparsed = parse_response(&transcript).unwrap();
+        assert_eq!(reparsed.tool_calls, vec![call]);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_backend_from_file() {
+        let dir = crate::tools::workspace::test_dir("scripted");
+        let path = dir.join("responses.json");
+        std::fs::write(&path, r#"[
+            {"text": "파일 목록을 봅니다.", "tool_calls": [{"name": "list_directory", "arguments": {"path": "."}}]},
+            {"text": "최종 보고서"}
+        ]"#).unwrap();
+        let backend = ScriptedBackend::from_file(&path).unwrap();
+
+        let messages = vec![ChatMessage::user("시작")];
+        let first = backend.send(&messages, &[]).await.unwrap();
+        assert_eq!(first.tool_calls[0].name, "list_directory");
+        let second = backend.send(&messages, &[]).await.unwrap();
+        assert!(second.tool_calls.is_empty());
+        assert_eq!(backend.remaining(), 0);
+        assert_eq!(backend.requests().len(), 2);
+
+        // 준비된 응답을 다 쓰면 에러
+        assert!(backend.send(&messages, &[]).await.is_err());
+    }
+}
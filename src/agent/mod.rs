@@ -2,9 +2,9 @@
 //
 // 이 모듈은 Claude SDK를 사용해 대화형 취약점 분석을 수행합니다.
 
-// TODO: 하위 모듈 선언
-// pub mod orchestrator;  // 메인 에이전트 로직
-// pub mod prompt;        // 프롬프트 템플릿
+pub mod llm;           // 모델 백엔드 (Claude, 스크립트 재생)
+pub mod orchestrator;  // 메인 에이전트 로직
+pub mod prompt;        // 프롬프트 템플릿
 
 // Rust 학습 포인트:
 // - `pub mod`는 공개 모듈 선언 (다른 모듈에서 사용 가능)
@@ -2,45 +2,166 @@
 //
 // 이 파일은 Claude와의 대화를 관리하고, CodeQL 도구를 호출하는 역할을 합니다.
 
-// TODO: 필요한 타입들 가져오기
-// use anyhow::Result;
-// use crate::tools::codeql::CodeQLRunner;
-
-// Rust 학습 포인트:
-// - `struct`로 데이터 구조 정의
-// - `impl`로 메서드 구현
-// - `async fn`으로 비동기 함수 작성
-
-// TODO: 에이전트 구조체 정의
-// pub struct VulnerabilityAgent {
-//     // Claude API 클라이언트
-//     // CodeQL Runner
-//     // 현재 분석 중인 소스코드 경로
-// }
-
-// TODO: 구현해야 할 메서드들
-// impl VulnerabilityAgent {
-//     
-//     // 1. 새 에이전트 인스턴스 생성
-//     // pub fn new(api_key: String) -> Result<Self> { }
-//     
-//     // 2. 취약점 분석 시작
-//     // pub async fn analyze(&self, source_path: &str) -> Result<String> {
-//     //     // 단계:
-//     //     // a) Claude에게 분석 계획 요청
-//     //     // b) 필요한 CodeQL 쿼리 결정
-//     //     // c) CodeQL 실행 (tools::codeql 사용)
-//     //     // d) 결과를 Claude에게 전달해 해석 요청
-//     //     // e) 최종 보고서 반환
-//     // }
-//     
-//     // 3. Claude와 대화하기
-//     // async fn chat_with_claude(&self, message: &str) -> Result<String> { }
-//     
-//     // 4. CodeQL 도구 호출 결정
-//     // fn should_use_codeql(&self, claude_response: &str) -> bool { }
-// }
-
-pub fn placeholder() {
-    println!("orchestrator.rs - 여기에 에이전트 로직을 구현하세요!");
+use std::path::PathBuf;
+use serde_json::Value;
+use crate::agent::llm::{ChatMessage, LlmBackend};
+use crate::agent::prompt::{task_prompt, tool_result_prompt, tool_error_prompt};
+use crate::error::{AppError, Result};
+use crate::tools::ToolRegistry;
+
+const DEFAULT_MAX_TURNS: usize = 20;
+
+pub struct VulnerabilityAgent {
+    backend: Box<dyn LlmBackend>,
+    tools: ToolRegistry,
+    source_root: PathBuf,
+    max_turns: usize,
+}
+
+impl VulnerabilityAgent {
+    pub fn new(backend: Box<dyn LlmBackend>, tools: ToolRegistry, source_root: impl Into<PathBuf>) -> Self {
+        VulnerabilityAgent {
+            backend,
+            tools,
+            source_root: source_root.into(),
+            max_turns: DEFAULT_MAX_TURNS,
+        }
+    }
+
+    pub fn with_max_turns(mut self, max_turns: usize) -> Self {
+        self.max_turns = max_turns;
+        self
+    }
+
+    /// 취약점 분석을 실행하고 최종 보고서를 반환
+    ///
+    /// 모델 응답에 도구 호출이 있으면 실행해서 결과를 돌려주고,
+    /// 도구 호출이 없는 응답이 나오면 그것을 최종 보고서로 보고 종료합니다.
+    pub async fn analyze(&self, task: &str) -> Result<String> {
+        let tools = self.tools.specs();
+        let mut messages = vec![ChatMessage::user(task_prompt(
+            &self.source_root.display().to_string(),
+            task,
+        ))];
+
+        for _ in 0..self.max_turns {
+            let response = match self.backend.send(&messages, &tools).await {
+                Ok(response) => response,
+                // 도구 블록이 깨진 응답은 오류를 알려주고 다시 요청
+                Err(AppError::ToolCallParseError { reply, message }) => {
+                    messages.push(ChatMessage::assistant(reply, Vec::new()));
+                    messages.push(ChatMessage::user(tool_error_prompt("tool", &message)));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            messages.push(ChatMessage::assistant(response.text.clone(), response.tool_calls.clone()));
+
+            if response.tool_calls.is_empty() {
+                return Ok(response.text);
+            }
+
+            let mut results = Vec::new();
+            for call in &response.tool_calls {
+                // 도구 실패는 분석 전체를 멈추지 않고 모델에게 알려서 다시 시도하게 함
+                let content = match self.tools.invoke(&call.name, call.arguments.clone()).await {
+                    Ok(output) => tool_result_prompt(&call.name, &render_output(&output)),
+                    Err(e) => tool_error_prompt(&call.name, &e.to_string()),
+                };
+                results.push(content);
+            }
+            messages.push(ChatMessage::user(results.join("\n\n")));
+        }
+
+        Err(AppError::AgentError(format!(
+            "No final report after {} turns",
+            self.max_turns
+        )))
+    }
+}
+
+// 문자열 결과(파일 내용 등)는 그대로, 나머지는 보기 좋은 JSON으로
+fn render_output(output: &Value) -> String {
+    match output {
+        Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use serde_json::json;
+    use crate::agent::llm::{LlmResponse, ScriptedBackend, ScriptedReply, ToolCall};
+
+    fn tool_call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            name: name.to_string(),
+            arguments,
+        }
+    }
+
+    fn agent(backend: Arc<ScriptedBackend>) -> VulnerabilityAgent {
+        let mut tools = ToolRegistry::new();
+        tools.register_filesystem_tools("./src");
+        VulnerabilityAgent::new(Box::new(backend), tools, "./src").with_max_turns(3)
+    }
+
+    #[tokio::test]
+    async fn test_analyze_runs_tools_until_final_report() {
+        let backend = Arc::new(ScriptedBackend::new(vec![
+            LlmResponse {
+                text: "파일을 읽습니다.".to_string(),
+                tool_calls: vec![
+                    tool_call("read_file_lines", json!({"path": "lib.rs", "startline": 1, "endline": 1})),
+                    tool_call("no_such_tool", json!({})),
+                ],
+            },
+            LlmResponse {
+                text: "## 보고서\n취약점 없음".to_string(),
+                tool_calls: vec![],
+            },
+        ]));
+
+        let report = agent(backend.clone()).analyze("분석해줘").await.unwrap();
+        assert_eq!(report, "## 보고서\n취약점 없음");
+
+        // 두 번째 요청에 도구 결과와 오류가 모두 전달되어야 함
+        let requests = backend.requests();
+        assert_eq!(requests.len(), 2);
+        let feedback = &requests[1].last().unwrap().content;
+        assert!(feedback.contains("[도구 결과: read_file_lines]\n// CodeQL Agent"));
+        assert!(feedback.contains("[도구 오류: no_such_tool]"));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_recovers_from_broken_tool_block() {
+        let backend = Arc::new(ScriptedBackend::from_replies(vec![
+            ScriptedReply::Raw("파일을 읽습니다.\n```tool\n{\"name\": \"read_file\", \"arguments\": {\"path\": \"lib.rs\"}".to_string()),
+            ScriptedReply::Raw("## 보고서\n취약점 없음".to_string()),
+        ]));
+
+        let report = agent(backend.clone()).analyze("분석해줘").await.unwrap();
+        assert_eq!(report, "## 보고서\n취약점 없음");
+
+        // 깨진 응답 원문과 파싱 오류가 다음 요청에 들어가야 함
+        let requests = backend.requests();
+        assert_eq!(requests.len(), 2);
+        let retry = &requests[1];
+        assert!(retry[retry.len() - 2].content.starts_with("파일을 읽습니다."));
+        let feedback = &retry.last().unwrap().content;
+        assert!(feedback.contains("[도구 오류: tool]\nUnterminated tool block"));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_stops_after_max_turns() {
+        let looping = LlmResponse {
+            text: String::new(),
+            tool_calls: vec![tool_call("list_directory", json!({}))],
+        };
+        let backend = Arc::new(ScriptedBackend::new(vec![looping.clone(), looping.clone(), looping]));
+        let result = agent(backend).analyze("분석해줘").await;
+        assert!(matches!(result, Err(AppError::AgentError(_))));
+    }
 }
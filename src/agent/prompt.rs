@@ -7,32 +7,48 @@
 // - 문자열 리터럴 `&str` vs 소유 문자열 `String`
 // - 여러 줄 문자열은 `r#"...내용..."#` 사용 가능
 
-// TODO: 시스템 프롬프트 작성
-// pub const SYSTEM_PROMPT: &str = r#"
-// 당신은 소스코드 보안 전문가입니다.
-// 주어진 소스코드의 취약점을 분석하고 보고서를 작성합니다.
-// 
-// 사용 가능한 도구:
-// 1. CodeQL - 코드의 데이터 흐름과 제어 흐름을 분석
-//    - SQL injection 탐지
-//    - XSS 취약점 탐지
-//    - 경로 순회 취약점 탐지
-//    등등...
-// 
-// 분석 절차:
-// 1. 코드베이스 개요 파악
-// 2. 의심스러운 패턴 식별
-// 3. CodeQL 쿼리 선택 및 실행
-// 4. 결과 해석 및 위험도 평가
-// 5. 수정 방법 제안
-// "#;
-
-// TODO: 도구 사용 지시 프롬프트
-// pub fn get_codeql_instruction(query_type: &str) -> String {
-//     // CodeQL을 어떻게 사용할지 알려주는 프롬프트
-//     // 예: "다음 쿼리를 실행해주세요: sql-injection.ql"
-// }
-
-pub fn placeholder() {
-    println!("prompt.rs - 여기에 프롬프트 템플릿을 작성하세요!");
+use crate::agent::llm::ToolSpec;
+
+pub const SYSTEM_PROMPT: &str = r#"당신은 소스코드 보안 전문가입니다.
+주어진 소스코드의 취약점을 분석하고 보고서를 작성합니다.
+
+도구를 호출하려면 아래 형식의 코드 블록을 응답에 포함하세요. 한 응답에 여러 개를 넣어도 됩니다.
+```tool
+{"name": "read_file", "arguments": {"path": "src/main.c"}}
+```
+도구 결과는 다음 메시지로 전달됩니다.
+
+분석 절차:
+1. 코드베이스 개요 파악
+2. 의심스러운 패턴 식별
+3. 도구로 정의, 구현, 호출 관계 확인
+4. 결과 해석 및 위험도 평가
+5. 수정 방법 제안
+
+분석이 끝나면 도구 블록 없이 최종 보고서만 작성하세요.
+도구 블록이 없는 응답은 최종 보고서로 간주됩니다.
+"#;
+
+/// 사용 가능한 도구 목록을 시스템 프롬프트에 붙일 형태로 만듦
+pub fn tools_prompt(tools: &[ToolSpec]) -> String {
+    let mut text = String::from("사용 가능한 도구 (경로는 모두 소스코드 루트 기준 상대 경로):\n");
+    for tool in tools {
+        text.push_str(&format!("- {}: {}\n  인자 스키마: {}\n", tool.name, tool.description, tool.parameters));
+    }
+    text
+}
+
+/// 분석을 시작할 때 보내는 첫 메시지
+pub fn task_prompt(source_root: &str, task: &str) -> String {
+    format!("분석 대상 소스코드 루트: {}\n\n요청: {}", source_root, task)
+}
+
+/// 도구 실행 결과를 모델에게 돌려주는 메시지
+pub fn tool_result_prompt(name: &str, result: &str) -> String {
+    format!("[도구 결과: {}]\n{}", name, result)
+}
+
+/// 도구 실행이 실패했을 때 모델에게 돌려주는 메시지
+pub fn tool_error_prompt(name: &str, error: &str) -> String {
+    format!("[도구 오류: {}]\n{}\n인자를 확인하고 다시 시도하거나 다른 방법을 사용하세요.", name, error)
 }
@@ -1,4 +1,5 @@
 use thiserror::Error;
+use crate::tools::language::Language;
 
 pub type Result<T> = std::result::Result<T, AppError>;
 
@@ -13,6 +14,51 @@ pub enum AppError {
     #[error("CodeQL error: {0}")]
     CodeQLError(String),
 
+    #[error(
+        "codeql database create failed (exit code {}), see logs in {}\n{stderr}",
+        .exit_code.map_or_else(|| "none".to_string(), |code| code.to_string()),
+        .log_dir.display()
+    )]
+    DatabaseCreateError {
+        exit_code: Option<i32>,
+        stderr: String,
+        log_dir: std::path::PathBuf,
+    },
+
+    #[error("{operation} timed out after {}s", .timeout.as_secs_f64())]
+    Timeout {
+        operation: String,
+        timeout: std::time::Duration,
+    },
+
+    #[error("Query imports {query} but the database language is {database}")]
+    LanguageMismatch {
+        database: Language,
+        query: Language,
+    },
+
+    #[error("Unsupported language: {0}")]
+    UnsupportedLanguage(String),
+
     #[error("CSV parsing error: {0}")]
     CsvError(#[from] csv::Error),
+
+    #[error("Claude error: {0}")]
+    ClaudeError(#[from] claude_sdk_rs::Error),
+
+    #[error("LLM backend error: {0}")]
+    LlmError(String),
+
+    #[error("Invalid tool call: {message}")]
+    ToolCallParseError {
+        // 도구 블록을 포함한 모델 응답 원문
+        reply: String,
+        message: String,
+    },
+
+    #[error("Agent error: {0}")]
+    AgentError(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
 }
@@ -1,40 +1,148 @@
 // CodeQL Agent - 소스코드 취약점 분석 에이전트
-// 
+//
 // 이 프로젝트는 Claude AI와 CodeQL을 결합한 취약점 분석 도구입니다.
-// Rust를 배우면서 단계별로 구현해 나가세요!
 
-// TODO: 필요한 모듈들을 가져오기
-use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use clap::{Parser, ValueEnum};
 use anyhow::Result;
-
-// 필요할 때 lib.rs의 모듈을 사용: use codeql_agent::{agent, tools};
+use codeql_agent::agent::llm::{ClaudeBackend, LlmBackend, ScriptedBackend};
+use codeql_agent::agent::orchestrator::VulnerabilityAgent;
+use codeql_agent::tools::ToolRegistry;
+use codeql_agent::tools::cache::{QueryCache, DEFAULT_MAX_BYTES};
+use codeql_agent::tools::pack::QueryPacks;
+use codeql_agent::tools::workspace::{KeepArtifacts, Workspace};
+use codeql_agent::tools::codeql::{CodeQLAnalyzer, CodeQLRunner};
 
 #[derive(Parser)]
 #[command(name = "codeql_agent", about = "소스코드 취약점 분석 에이전트")]
 pub struct Args {
     #[arg(short, long)]
     pub source: String,
+
+    /// CodeQL 데이터베이스 경로
+    #[arg(short, long)]
+    pub database: String,
+
+    /// 에이전트에게 줄 분석 요청
+    #[arg(short, long, default_value = "이 코드베이스에서 메모리 안전성 취약점을 찾아 보고서를 작성하세요.")]
+    pub task: String,
+
+    /// 사용할 Claude 모델 (생략하면 CLI 기본값)
+    #[arg(long)]
+    pub model: Option<String>,
+
+    /// Claude 대신 파일에 준비된 응답을 재생 (오프라인 실행용)
+    #[arg(long)]
+    pub replay: Option<String>,
+
+    /// 모델과 주고받을 최대 턴 수
+    #[arg(long, default_value_t = 20)]
+    pub max_turns: usize,
+
+    /// CodeQL 쿼리 하나의 제한 시간(초), 0이면 제한 없음
+    #[arg(long, default_value_t = 300)]
+    pub query_timeout: u64,
+
+    /// query-server2를 쓰지 않고 쿼리마다 codeql query run 실행
+    #[arg(long)]
+    pub no_query_server: bool,
+
+    /// 쿼리 결과 캐시를 쓰지 않음
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// 쿼리 결과 캐시 디렉터리 (기본: 시스템 임시 디렉터리/codeql_agent_cache)
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// 쿼리 결과 캐시 최대 크기(바이트)
+    #[arg(long, default_value_t = DEFAULT_MAX_BYTES)]
+    pub cache_max_bytes: u64,
+
+    /// 쿼리 작업 디렉터리를 만들 위치 (기본: 시스템 임시 디렉터리/codeql_agent)
+    #[arg(long)]
+    pub scratch_dir: Option<PathBuf>,
+
+    /// 쿼리 작업 파일(query.ql, bqrs, 로그)을 남길 조건
+    #[arg(long, value_enum, default_value_t = KeepArtifactsArg::Never)]
+    pub keep_artifacts: KeepArtifactsArg,
+
+    /// 언어별 쿼리 팩을 둘 디렉터리 (기본: 시스템 임시 디렉터리/codeql_agent/packs)
+    #[arg(long)]
+    pub pack_dir: Option<PathBuf>,
+
+    /// codeql pack install 없이 로컬 팩 캐시만 사용
+    #[arg(long)]
+    pub offline: bool,
+
+    /// 라이브러리 팩을 찾을 추가 디렉터리 (여러 번 지정 가능)
+    #[arg(long)]
+    pub additional_packs: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum KeepArtifactsArg {
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl From<KeepArtifactsArg> for KeepArtifacts {
+    fn from(arg: KeepArtifactsArg) -> Self {
+        match arg {
+            KeepArtifactsArg::Never => KeepArtifacts::Never,
+            KeepArtifactsArg::OnFailure => KeepArtifacts::OnFailure,
+            KeepArtifactsArg::Always => KeepArtifacts::Always,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // TODO: 1단계 - 기본 CLI 파라미터 파싱
-    // - clap을 사용해서 소스코드 경로를 받을 수 있게 만들기
-    // - 예: cargo run -- analyze ./my-project
-    let args = Args::parse();    
+    let args = Args::parse();
     println!("소스코드 경로: {}", args.source);
 
+    let query_timeout = (args.query_timeout > 0).then(|| Duration::from_secs(args.query_timeout));
+    let mut runner = CodeQLRunner::new(&args.source, &args.database)?
+        .with_query_timeout(query_timeout)
+        .with_query_server(!args.no_query_server);
+    let workspace = match &args.scratch_dir {
+        Some(dir) => Workspace::new(dir),
+        None => Workspace::default(),
+    };
+    runner = runner.with_workspace(workspace.with_keep(args.keep_artifacts.into()));
+    let packs = match &args.pack_dir {
+        Some(dir) => QueryPacks::new(dir),
+        None => QueryPacks::default(),
+    };
+    runner = runner.with_packs(
+        packs
+            .with_offline(args.offline)
+            .with_additional_packs(args.additional_packs.clone()),
+    );
+    if !args.no_cache {
+        let cache_dir = args.cache_dir.clone().unwrap_or_else(QueryCache::default_dir);
+        runner = runner.with_cache(QueryCache::new(cache_dir, args.cache_max_bytes)?);
+    }
+    let analyzer = Arc::new(CodeQLAnalyzer::new(runner));
+    let tools = ToolRegistry::with_builtin(analyzer.clone(), &args.source);
+
+    let backend: Box<dyn LlmBackend> = match (args.replay, args.model) {
+        (Some(path), _) => Box::new(ScriptedBackend::from_file(path)?),
+        (None, Some(model)) => Box::new(ClaudeBackend::new().with_model(model)),
+        (None, None) => Box::new(ClaudeBackend::new()),
+    };
+
+    let agent = VulnerabilityAgent::new(backend, tools, &args.source)
+        .with_max_turns(args.max_turns);
 
-    // TODO: 2단계 - 비동기 런타임 설정
-    // - tokio를 사용해 async/await 지원
-    // - main 함수를 #[tokio::main]으로 변경
+    let report = agent.analyze(&args.task).await?;
+    println!("{}", report);
 
-    
-    // TODO: 3단계 - 에이전트 초기화 및 실행
-    // - agent::orchestrator 모듈의 함수 호출
-    // - 결과를 받아서 출력
-    
-    println!("CodeQL Agent - 아직 구현 전입니다!");
-    println!("main.rs의 TODO를 확인하고 하나씩 구현해보세요.");
+    if let Some(stats) = analyzer.runner().cache_stats() {
+        eprintln!("쿼리 캐시: 적중 {}회, 미스 {}회", stats.hits, stats.misses);
+    }
     Ok(())
 }
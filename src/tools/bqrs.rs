@@ -0,0 +1,303 @@
+// bqrs.rs - `codeql bqrs decode --format=json` 결과 모델
+//
+// CSV는 컬럼 이름만 남고 엔티티 종류, 위치, 여러 결과 집합이 사라집니다.
+// JSON으로 디코딩하면 컬럼 타입과 엔티티 위치를 그대로 쓸 수 있습니다.
+
+use std::collections::BTreeMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use crate::error::{AppError, Result};
+
+/// select 문의 기본 결과 집합 이름
+pub const DEFAULT_RESULT_SET: &str = "#select";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnKind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Date,
+    Entity,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+    // `as`로 이름을 붙이지 않은 컬럼은 이름이 없음
+    #[serde(default)]
+    pub name: String,
+    pub kind: ColumnKind,
+}
+
+/// 엔티티의 소스 위치
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    /// `file://` 없이 데이터베이스에 기록된 절대 경로
+    pub path: String,
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl Location {
+    /// 소스코드 루트 기준 상대 경로. 루트 밖이면 None
+    pub fn relative_path(&self, source_root: &str) -> Option<String> {
+        let root = source_root.trim_end_matches('/');
+        self.path
+            .strip_prefix(root)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .map(str::to_string)
+    }
+
+    // 예전 CLI의 "file:///src/a.c:1:2:3:4" 형식
+    fn parse_url_string(url: &str) -> Option<Self> {
+        let mut parts = url.rsplitn(5, ':');
+        let end_column = parts.next()?.parse().ok()?;
+        let end_line = parts.next()?.parse().ok()?;
+        let start_column = parts.next()?.parse().ok()?;
+        let start_line = parts.next()?.parse().ok()?;
+        Some(Location {
+            path: strip_file_scheme(parts.next()?),
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        })
+    }
+}
+
+fn strip_file_scheme(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").or_else(|| uri.strip_prefix("file:")).unwrap_or(uri);
+    path.to_string()
+}
+
+impl<'de> Deserialize<'de> for Location {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct UrlObject {
+            // Location을 다시 직렬화한 값도 읽을 수 있게 path 허용
+            #[serde(alias = "path")]
+            uri: String,
+            #[serde(default)]
+            start_line: u32,
+            #[serde(default)]
+            start_column: u32,
+            #[serde(default)]
+            end_line: u32,
+            #[serde(default)]
+            end_column: u32,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Url {
+            Object(UrlObject),
+            Text(String),
+        }
+
+        match Url::deserialize(deserializer)? {
+            Url::Object(url) => Ok(Location {
+                path: strip_file_scheme(&url.uri),
+                start_line: url.start_line,
+                start_column: url.start_column,
+                end_line: url.end_line,
+                end_column: url.end_column,
+            }),
+            Url::Text(text) => Location::parse_url_string(&text)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid location url: {}", text))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    #[serde(default)]
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub label: String,
+    // 위치가 없는 엔티티(타입 등)도 있음
+    #[serde(default, rename = "url", skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+}
+
+/// 튜플의 값 하나
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Cell {
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Entity(Entity),
+}
+
+impl Cell {
+    pub fn as_entity(&self) -> Option<&Entity> {
+        match self {
+            Cell::Entity(entity) => Some(entity),
+            _ => None,
+        }
+    }
+
+    // 엔티티는 label, location을 가진 객체로, 나머지는 값 그대로
+    fn to_json(&self) -> Value {
+        match self {
+            Cell::Boolean(value) => Value::Bool(*value),
+            Cell::Integer(value) => Value::from(*value),
+            Cell::Float(value) => Value::from(*value),
+            Cell::String(value) => Value::String(value.clone()),
+            Cell::Entity(entity) => serde_json::to_value(entity).unwrap_or(Value::Null),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultSet {
+    pub columns: Vec<Column>,
+    pub tuples: Vec<Vec<Cell>>,
+}
+
+impl ResultSet {
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column.name == name)
+    }
+
+    /// 해당 행, 컬럼의 엔티티 위치
+    pub fn location(&self, row: usize, column: &str) -> Option<&Location> {
+        let index = self.column_index(column)?;
+        self.tuples.get(row)?.get(index)?.as_entity()?.location.as_ref()
+    }
+
+    /// 컬럼 이름을 필드 이름으로 해서 각 행을 T로 변환 (parse_csv와 같은 용도)
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<Vec<T>> {
+        self.tuples
+            .iter()
+            .map(|tuple| {
+                let row: Map<String, Value> = self.columns
+                    .iter()
+                    .zip(tuple)
+                    .map(|(column, cell)| (column.name.clone(), cell.to_json()))
+                    .collect();
+                Ok(serde_json::from_value(Value::Object(row))?)
+            })
+            .collect()
+    }
+}
+
+/// bqrs 파일 하나의 모든 결과 집합
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BqrsResults {
+    pub result_sets: BTreeMap<String, ResultSet>,
+}
+
+impl BqrsResults {
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// 이름으로 결과 집합 선택, None이면 `#select`
+    pub fn result_set(&self, name: Option<&str>) -> Result<&ResultSet> {
+        let name = name.unwrap_or(DEFAULT_RESULT_SET);
+        self.result_sets.get(name).ok_or_else(|| self.missing(name))
+    }
+
+    pub fn into_result_set(mut self, name: Option<&str>) -> Result<ResultSet> {
+        let name = name.unwrap_or(DEFAULT_RESULT_SET);
+        match self.result_sets.remove(name) {
+            Some(result_set) => Ok(result_set),
+            None => Err(self.missing(name)),
+        }
+    }
+
+    fn missing(&self, name: &str) -> AppError {
+        AppError::CodeQLError(format!(
+            "No result set named {} (available: {})",
+            name,
+            self.result_sets.keys().cloned().collect::<Vec<_>>().join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECODED: &str = r##"{
+        "#select": {
+            "columns": [
+                {"name": "f", "kind": "Entity"},
+                {"name": "filename", "kind": "String"},
+                {"name": "startline", "kind": "Integer"},
+                {"kind": "Boolean"}
+            ],
+            "tuples": [
+                [
+                    {"id": 42, "label": "parse_url", "url": {"uri": "file:///src/curl/lib/urlapi.c", "startLine": 10, "startColumn": 1, "endLine": 80, "endColumn": 1}},
+                    "lib/urlapi.c", 10, true
+                ],
+                [
+                    {"id": 43, "label": "int"},
+                    "lib/a.c", 3, false
+                ]
+            ]
+        },
+        "edges": {
+            "columns": [{"name": "a", "kind": "Entity"}, {"name": "b", "kind": "Entity"}],
+            "tuples": [[
+                {"label": "x", "url": "file:/src/curl/lib/a.c:3:5:3:9"},
+                {"label": "y", "url": "file:/src/curl/lib/a.c:4:1:4:2"}
+            ]]
+        }
+    }"##;
+
+    #[test]
+    fn test_decode_typed_columns() {
+        let results = BqrsResults::parse(DECODED).unwrap();
+        let select = results.result_set(None).unwrap();
+        assert_eq!(select.columns[0], Column { name: "f".to_string(), kind: ColumnKind::Entity });
+        assert_eq!(select.columns[3].name, "");
+        assert_eq!(select.tuples[0][2], Cell::Integer(10));
+        assert_eq!(select.tuples[1][3], Cell::Boolean(false));
+
+        let location = select.location(0, "f").unwrap();
+        assert_eq!(location.path, "/src/curl/lib/urlapi.c");
+        assert_eq!((location.start_line, location.end_line), (10, 80));
+        assert_eq!(location.relative_path("/src/curl/").as_deref(), Some("lib/urlapi.c"));
+        assert_eq!(location.relative_path("/other"), None);
+        assert_eq!(select.location(1, "f"), None);
+    }
+
+    #[test]
+    fn test_select_result_set_and_old_url_format() {
+        let results = BqrsResults::parse(DECODED).unwrap();
+        let edges = results.result_set(Some("edges")).unwrap();
+        let location = edges.location(0, "b").unwrap();
+        assert_eq!(location.path, "/src/curl/lib/a.c");
+        assert_eq!((location.start_line, location.start_column, location.end_column), (4, 1, 2));
+        assert!(results.result_set(Some("nodes")).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_rows() {
+        #[derive(Deserialize)]
+        struct Row {
+            f: Entity,
+            filename: String,
+            startline: u32,
+        }
+
+        let select = BqrsResults::parse(DECODED).unwrap().into_result_set(None).unwrap();
+        let rows: Vec<Row> = select.deserialize().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].f.label, "parse_url");
+        assert_eq!(rows[0].f.location.as_ref().unwrap().start_line, 10);
+        assert_eq!((rows[1].filename.as_str(), rows[1].startline), ("lib/a.c", 3));
+    }
+}
@@ -0,0 +1,713 @@
+// builtin.rs - CodeQLAnalyzer와 FileSystem 메서드를 모델용 도구로 노출
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use crate::error::{AppError, Result};
+use crate::tools::{Tool, ToolRegistry};
+use crate::tools::callgraph::{CallDirection, MAX_DEPTH};
+use crate::tools::codeql::CodeQLAnalyzer;
+use crate::tools::filesystem::FileSystem;
+use crate::tools::queries::FlowSpec;
+use crate::tools::symbols::{SymbolKind, SymbolPattern};
+
+impl ToolRegistry {
+    /// CodeQL 도구와 파일 시스템 도구를 모두 등록한 레지스트리
+    pub fn with_builtin(analyzer: Arc<CodeQLAnalyzer>, source_root: impl Into<PathBuf>) -> Self {
+        let mut registry = ToolRegistry::new();
+        registry.register_codeql_tools(analyzer);
+        registry.register_filesystem_tools(source_root);
+        registry
+    }
+
+    pub fn register_codeql_tools(&mut self, analyzer: Arc<CodeQLAnalyzer>) -> &mut Self {
+        self.register(FindVarDefinitionsTool { analyzer: analyzer.clone() });
+        self.register(FindVarRefsTool { analyzer: analyzer.clone() });
+        self.register(FindFunctionImplementationTool { analyzer: analyzer.clone() });
+        self.register(FindFunctionCodeTool { analyzer: analyzer.clone() });
+        self.register(FindFunctionRefsTool { analyzer: analyzer.clone() });
+        self.register(SummarizeFunctionTool { analyzer: analyzer.clone() });
+        self.register(SearchSymbolsTool { analyzer: analyzer.clone() });
+        self.register(FindMacroTool { analyzer: analyzer.clone() });
+        self.register(FindTypeDefinitionTool { analyzer: analyzer.clone() });
+        self.register(FindDataflowTool { analyzer: analyzer.clone() });
+        self.register(CallGraphTool { analyzer: analyzer.clone() });
+        self.register(AnalyzeSuiteTool { analyzer });
+        self
+    }
+
+    /// 파일 시스템 도구 등록. 모든 경로는 source_root 안으로 제한됨
+    pub fn register_filesystem_tools(&mut self, source_root: impl Into<PathBuf>) -> &mut Self {
+        let root = SourceRoot(Arc::new(source_root.into()));
+        self.register(ReadFileTool { root: root.clone() });
+        self.register(ReadFileLinesTool { root: root.clone() });
+        self.register(ListDirectoryTool { root });
+        self
+    }
+}
+
+fn parse_args<T: DeserializeOwned>(tool: &str, args: Value) -> Result<T> {
+    serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidArgument(format!("{}: {}", tool, e)))
+}
+
+fn location_schema(name: &str, description: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "filename": {"type": "string", "description": "소스코드 루트 기준 상대 경로"},
+            "line": {"type": "integer", "minimum": 1},
+            "column": {"type": "integer", "minimum": 1, "description": "같은 라인에 후보가 여러 개일 때 구분용 열 (생략 가능)"},
+            name: {"type": "string", "description": description}
+        },
+        "required": ["filename", "line", name]
+    })
+}
+
+fn func_name_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "filename": {"type": "string", "description": "함수가 정의된 파일 (생략 가능, 같은 이름의 static 함수 구분용)"},
+            "funcname": {"type": "string", "description": "함수명"}
+        },
+        "required": ["funcname"]
+    })
+}
+
+#[derive(Clone)]
+struct SourceRoot(Arc<PathBuf>);
+
+impl SourceRoot {
+    // 모델이 넘긴 경로를 소스코드 루트 안으로 제한.
+    // 루트 안의 심볼릭 링크가 바깥을 가리킬 수 있으므로 실제 경로로 다시 확인
+    fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let path = Path::new(path);
+        let resolved = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.0.join(path)
+        };
+        let outside = || AppError::InvalidArgument(format!(
+            "Path is outside of the source root: {}",
+            path.display()
+        ));
+
+        if resolved.components().any(|c| c == Component::ParentDir)
+            || !resolved.starts_with(self.0.as_path())
+        {
+            return Err(outside());
+        }
+
+        let canonical = resolved.canonicalize()?;
+        if !canonical.starts_with(self.0.canonicalize()?) {
+            return Err(outside());
+        }
+        Ok(canonical)
+    }
+}
+
+#[derive(Deserialize)]
+struct VarArgs {
+    filename: String,
+    line: u32,
+    #[serde(default)]
+    column: Option<u32>,
+    varname: String,
+}
+
+#[derive(Deserialize)]
+struct FuncArgs {
+    filename: String,
+    line: u32,
+    #[serde(default)]
+    column: Option<u32>,
+    funcname: String,
+}
+
+#[derive(Deserialize)]
+struct FuncNameArgs {
+    #[serde(default)]
+    filename: Option<String>,
+    funcname: String,
+}
+
+#[derive(Deserialize)]
+struct SymbolArgs {
+    pattern: String,
+    #[serde(default)]
+    regex: bool,
+    #[serde(default)]
+    kinds: Vec<SymbolKind>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct MacroArgs {
+    filename: String,
+    line: u32,
+    #[serde(default)]
+    column: Option<u32>,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct TypeArgs {
+    filename: String,
+    line: u32,
+    #[serde(default)]
+    column: Option<u32>,
+    typename: String,
+}
+
+#[derive(Deserialize)]
+struct DataflowArgs {
+    source: FlowSpec,
+    sink: FlowSpec,
+}
+
+#[derive(Deserialize)]
+struct CallGraphArgs {
+    function: String,
+    direction: CallDirection,
+    #[serde(default = "default_depth")]
+    depth: u32,
+    #[serde(default)]
+    dot: bool,
+}
+
+#[derive(Deserialize)]
+struct SuiteArgs {
+    suite: String,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct PathArgs {
+    #[serde(default = "default_path")]
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct LinesArgs {
+    path: String,
+    startline: u32,
+    endline: u32,
+}
+
+fn default_depth() -> u32 {
+    3
+}
+
+fn default_path() -> String {
+    ".".to_string()
+}
+
+fn default_limit() -> usize {
+    50
+}
+
+pub struct FindVarDefinitionsTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindVarDefinitionsTool {
+    fn name(&self) -> &str {
+        "find_var_definitions"
+    }
+
+    fn description(&self) -> &str {
+        "해당 위치에서 사용된 변수의 정의 위치와 코드를 배열로 반환. 후보가 여러 개면 종류(kind), 범위(scope), 가리는 변수(shadows)로 구분해서 column을 골라 다시 호출"
+    }
+
+    fn parameters(&self) -> Value {
+        location_schema("varname", "변수명")
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: VarArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_var_definitions(&args.filename, args.line, args.column, &args.varname).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct FindVarRefsTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindVarRefsTool {
+    fn name(&self) -> &str {
+        "find_var_refs"
+    }
+
+    fn description(&self) -> &str {
+        "해당 위치에서 사용된 변수의 모든 읽기, 쓰기, 주소 획득, 인자 전달 위치를 주변 코드와 함께 반환"
+    }
+
+    fn parameters(&self) -> Value {
+        location_schema("varname", "변수명")
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: VarArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_var_refs(&args.filename, args.line, args.column, &args.varname).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct FindFunctionImplementationTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindFunctionImplementationTool {
+    fn name(&self) -> &str {
+        "find_function_implementation"
+    }
+
+    fn description(&self) -> &str {
+        "해당 위치에서 호출된 함수의 구현 코드를 반환 (가상 함수면 오버라이드 포함). 각 결과의 kind가 function이면 함수, macro면 find_macro와 같은 매크로 정의"
+    }
+
+    fn parameters(&self) -> Value {
+        location_schema("funcname", "함수명")
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: FuncArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_function_implementation(&args.filename, args.line, args.column, &args.funcname).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct FindFunctionCodeTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindFunctionCodeTool {
+    fn name(&self) -> &str {
+        "find_function_code"
+    }
+
+    fn description(&self) -> &str {
+        "이름으로 함수 정의를 찾아 시그니처, 파일, 시작 라인, 코드를 반환 (오버로드, static 함수는 모두 반환)"
+    }
+
+    fn parameters(&self) -> Value {
+        func_name_schema()
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: FuncNameArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_function_code(args.filename.as_deref(), &args.funcname).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct FindFunctionRefsTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindFunctionRefsTool {
+    fn name(&self) -> &str {
+        "find_function_refs"
+    }
+
+    fn description(&self) -> &str {
+        "함수를 호출하는 모든 곳을 호출한 함수의 코드, 파일, 라인과 함께 반환"
+    }
+
+    fn parameters(&self) -> Value {
+        func_name_schema()
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: FuncNameArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_function_refs(args.filename.as_deref(), &args.funcname).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct SummarizeFunctionTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for SummarizeFunctionTool {
+    fn name(&self) -> &str {
+        "summarize_function"
+    }
+
+    fn description(&self) -> &str {
+        "함수 코드 대신 시그니처, 파라미터 타입, 지역 변수, 호출한 함수와 라인, return, 반복문, 메모리 할당 위치를 요약해서 반환. 긴 함수는 이걸로 먼저 보고 필요한 라인만 read_file_lines로 읽기"
+    }
+
+    fn parameters(&self) -> Value {
+        func_name_schema()
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: FuncNameArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.summarize_function(args.filename.as_deref(), &args.funcname).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct SearchSymbolsTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for SearchSymbolsTool {
+    fn name(&self) -> &str {
+        "search_symbols"
+    }
+
+    fn description(&self) -> &str {
+        "이름 일부나 glob/정규식으로 함수, 전역 변수, 타입, 매크로를 찾아 비슷한 순서로 파일, 라인과 함께 반환. 정확한 이름이나 위치를 모를 때 먼저 사용"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "pattern": {"type": "string", "description": "glob(*, ?)이나 이름 일부 (대소문자 무시), regex가 true면 이름 전체와 맞춰 볼 정규식"},
+                "regex": {"type": "boolean", "default": false},
+                "kinds": {
+                    "type": "array",
+                    "items": {"type": "string", "enum": ["function", "global", "type", "macro"]},
+                    "description": "찾을 종류 (생략하면 전부, macro는 C/C++만)"
+                },
+                "limit": {"type": "integer", "minimum": 1, "default": 50}
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: SymbolArgs = parse_args(self.name(), args)?;
+        let pattern = if args.regex { SymbolPattern::Regex(args.pattern) } else { SymbolPattern::Glob(args.pattern) };
+        let result = self.analyzer.search_symbols(&pattern, &args.kinds, args.limit).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct FindMacroTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindMacroTool {
+    fn name(&self) -> &str {
+        "find_macro"
+    }
+
+    fn description(&self) -> &str {
+        "해당 위치에서 쓰인 C/C++ 매크로의 #define 정의, 위치, 호출 인자와 CodeQL이 전개한 인자, 전개된 식과 타입을 반환. expansion은 body에 인자를 대입한 근사치(approximate)"
+    }
+
+    fn parameters(&self) -> Value {
+        location_schema("name", "매크로 이름")
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: MacroArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_macro(&args.filename, args.line, args.column, &args.name).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct FindTypeDefinitionTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindTypeDefinitionTool {
+    fn name(&self) -> &str {
+        "find_type_definition"
+    }
+
+    fn description(&self) -> &str {
+        "해당 위치에서 쓰인 타입의 typedef 체인을 따라가서 struct/class/union/enum 정의 코드, 멤버의 타입과 바이트 오프셋/크기, 베이스 클래스를 반환 (memcpy 크기 검토용). 정의는 요청한 파일의 것부터 최대 5개, 나머지는 omitted 개수로만 알려줌"
+    }
+
+    fn parameters(&self) -> Value {
+        location_schema("typename", "타입 이름 (struct 키워드 없이)")
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: TypeArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_type_definition(&args.filename, args.line, args.column, &args.typename).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+fn flow_spec_schema(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": {
+            "kind": {
+                "type": "string",
+                "enum": ["return_value", "parameter", "argument"],
+                "description": "return_value: 호출의 반환값(sink면 함수가 return 하는 값), parameter: 함수의 파라미터, argument: 호출의 인자(source면 호출이 인자에 써 넣은 값)"
+            },
+            "function": {"type": "string", "description": "함수 이름"},
+            "index": {"type": "integer", "minimum": 0, "description": "parameter, argument의 위치 (0부터)"}
+        },
+        "required": ["kind", "function"]
+    })
+}
+
+pub struct FindDataflowTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for FindDataflowTool {
+    fn name(&self) -> &str {
+        "find_dataflow"
+    }
+
+    fn description(&self) -> &str {
+        "source에서 sink로 데이터가 흘러가는지 taint tracking으로 확인하고 경로를 단계별 위치와 코드로 반환. 빈 배열이면 흐름 없음 (예: recv의 1번 인자 -> memcpy의 2번 인자)"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "source": flow_spec_schema("데이터가 시작되는 곳"),
+                "sink": flow_spec_schema("데이터가 도달하는지 확인할 곳")
+            },
+            "required": ["source", "sink"]
+        })
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: DataflowArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.find_dataflow(&args.source, &args.sink).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct CallGraphTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for CallGraphTool {
+    fn name(&self) -> &str {
+        "call_graph"
+    }
+
+    fn description(&self) -> &str {
+        "함수가 호출하는 함수들(callees) 또는 함수를 호출하는 함수들(callers)을 depth 단계까지 트리로 반환. 가상 함수와 함수 포인터 호출도 해석되는 만큼 포함 (main, 네트워크 핸들러에서의 도달 가능성 확인용)"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "function": {"type": "string", "description": "시작 함수 이름"},
+                "direction": {"type": "string", "enum": ["callers", "callees"]},
+                "depth": {"type": "integer", "minimum": 1, "maximum": MAX_DEPTH, "default": 3},
+                "dot": {"type": "boolean", "default": false, "description": "Graphviz DOT도 함께 반환"}
+            },
+            "required": ["function", "direction"]
+        })
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: CallGraphArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.call_graph(&args.function, args.direction, args.depth, args.dot).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct AnalyzeSuiteTool {
+    analyzer: Arc<CodeQLAnalyzer>,
+}
+
+#[async_trait]
+impl Tool for AnalyzeSuiteTool {
+    fn name(&self) -> &str {
+        "analyze_suite"
+    }
+
+    fn description(&self) -> &str {
+        "CodeQL 쿼리 스위트(예: cpp-security-extended.qls)를 실행해서 규칙, 심각도, 위치, 데이터 흐름이 포함된 결과 목록을 반환. 오래 걸리므로 분석 초반에 한 번만 사용"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "suite": {"type": "string", "description": "스위트 이름 또는 경로 (예: cpp-security-extended.qls)"},
+                "limit": {"type": "integer", "minimum": 1, "default": 50, "description": "반환할 최대 결과 수"}
+            },
+            "required": ["suite"]
+        })
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: SuiteArgs = parse_args(self.name(), args)?;
+        let result = self.analyzer.analyze_suite(&args.suite, args.limit).await?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+pub struct ReadFileTool {
+    root: SourceRoot,
+}
+
+#[async_trait]
+impl Tool for ReadFileTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn description(&self) -> &str {
+        "파일 전체 내용을 반환"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}},
+            "required": ["path"]
+        })
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: PathArgs = parse_args(self.name(), args)?;
+        let content = FileSystem::new().read_file(self.root.resolve(&args.path)?)?;
+        Ok(Value::String(content))
+    }
+}
+
+pub struct ReadFileLinesTool {
+    root: SourceRoot,
+}
+
+#[async_trait]
+impl Tool for ReadFileLinesTool {
+    fn name(&self) -> &str {
+        "read_file_lines"
+    }
+
+    fn description(&self) -> &str {
+        "파일의 일부 라인을 반환"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "startline": {"type": "integer", "minimum": 1},
+                "endline": {"type": "integer", "minimum": 1}
+            },
+            "required": ["path", "startline", "endline"]
+        })
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: LinesArgs = parse_args(self.name(), args)?;
+        let lines = FileSystem::new().read_file_lines(self.root.resolve(&args.path)?, args.startline, args.endline)?;
+        Ok(Value::String(lines.join("\n")))
+    }
+}
+
+pub struct ListDirectoryTool {
+    root: SourceRoot,
+}
+
+#[async_trait]
+impl Tool for ListDirectoryTool {
+    fn name(&self) -> &str {
+        "list_directory"
+    }
+
+    fn description(&self) -> &str {
+        "디렉터리 항목 목록을 반환"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {"path": {"type": "string", "default": "."}}
+        })
+    }
+
+    async fn invoke(&self, args: Value) -> Result<Value> {
+        let args: PathArgs = parse_args(self.name(), args)?;
+        let result = FileSystem::new().list_directory(self.root.resolve(&args.path)?)?;
+        Ok(serde_json::from_str(&result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::workspace::test_dir;
+
+    fn registry() -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        registry.register_filesystem_tools("./src");
+        registry
+    }
+
+    #[test]
+    fn test_filesystem_tool_specs() {
+        let registry = registry();
+        assert_eq!(registry.names(), vec!["read_file", "read_file_lines", "list_directory"]);
+        let spec = &registry.specs()[1];
+        assert_eq!(spec.parameters["required"], json!(["path", "startline", "endline"]));
+    }
+
+    #[tokio::test]
+    async fn test_read_file_lines_tool() {
+        let result = registry()
+            .invoke("read_file_lines", json!({"path": "lib.rs", "startline": 1, "endline": 1}))
+            .await
+            .unwrap();
+        assert!(result.as_str().unwrap().starts_with("// CodeQL Agent"));
+    }
+
+    #[tokio::test]
+    async fn test_path_outside_root_rejected() {
+        let registry = registry();
+        assert!(registry.invoke("read_file", json!({"path": "../Cargo.toml"})).await.is_err());
+        assert!(registry.invoke("read_file", json!({"path": "/etc/passwd"})).await.is_err());
+        // 인자가 빠지면 에러
+        assert!(registry.invoke("read_file_lines", json!({"path": "lib.rs"})).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_outside_root_rejected() {
+        let root = test_dir("source_root");
+        let outside = test_dir("source_outside");
+        std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+        std::fs::write(root.path().join("inside.txt"), "inside").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret.txt"), root.path().join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("linkdir")).unwrap();
+
+        let mut registry = ToolRegistry::new();
+        registry.register_filesystem_tools(root.path());
+        assert_eq!(registry.invoke("read_file", json!({"path": "inside.txt"})).await.unwrap(), json!("inside"));
+        assert!(registry.invoke("read_file", json!({"path": "link.txt"})).await.is_err());
+        assert!(registry.invoke("read_file", json!({"path": "linkdir/secret.txt"})).await.is_err());
+        assert!(registry.invoke("list_directory", json!({"path": "linkdir"})).await.is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// cache.rs - 쿼리 결과 디스크 캐시
+//
+// 에이전트는 같은 질문을 자주 반복하므로 디코딩한 결과를
+// (정규화한 쿼리, 데이터베이스 경로와 생성 정보, CodeQL 버전)의 해시로 저장해 둡니다.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use sha2::{Digest, Sha256};
+use crate::error::Result;
+
+/// 기본 캐시 크기 제한 (256MB)
+pub const DEFAULT_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+pub struct QueryCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(QueryCache {
+            dir,
+            max_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    /// 기본 위치(시스템 임시 디렉터리 아래)의 캐시
+    pub fn default_dir() -> PathBuf {
+        std::env::temp_dir().join("codeql_agent_cache")
+    }
+
+    /// 캐시 키. 들여쓰기와 빈 줄만 다른 쿼리는 같은 키가 됨
+    ///
+    /// db_fingerprint는 데이터베이스 경로와 codeql-database.yml 내용으로,
+    /// 데이터베이스를 다시 만들면 생성 시각이 바뀌어 이전 결과를 쓰지 않음.
+    /// pack_lock은 언어 팩의 codeql-pack.lock.yml 내용으로, 라이브러리 팩이 올라가면 키가 바뀜
+    pub fn key(query: &str, db_fingerprint: &str, codeql_version: &str, pack_lock: &str) -> String {
+        let mut hasher = Sha256::new();
+        for line in query.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            hasher.update(line.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.update(b"\0");
+        hasher.update(db_fingerprint.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(codeql_version.trim().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(pack_lock.as_bytes());
+        hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        let path = self.entry_path(key);
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                // 오래 안 쓴 항목부터 지우도록 사용 시각 갱신
+                if let Ok(file) = fs::File::options().append(true).open(&path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+                Some(content)
+            }
+            Err(_) => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// 결과를 저장하고 크기 제한을 넘으면 오래된 항목부터 삭제
+    pub fn put(&self, key: &str, content: &str) -> Result<()> {
+        if content.len() as u64 > self.max_bytes {
+            return Ok(());
+        }
+        // 다른 프로세스가 반쯤 쓴 파일을 읽지 않도록 임시 파일에 쓰고 이름을 바꿈
+        let path = self.entry_path(key);
+        let partial = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&partial, content)?;
+        fs::rename(&partial, &path)?;
+        self.evict()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.result", key))
+    }
+
+    fn evict(&self) -> Result<()> {
+        let mut entries = Vec::new();
+        let mut total = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("result") {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            total += metadata.len();
+            entries.push((metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), metadata.len(), path));
+        }
+
+        entries.sort();
+        for (_, size, path) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::tools::workspace::test_dir;
+
+    #[test]
+    fn test_key_normalization() {
+        let a = QueryCache::key("\n    import cpp\n\n    from Function f\n    select f\n", "db", "2.15.0", "lock");
+        let b = QueryCache::key("import cpp\nfrom Function f\n  select f", "db", "2.15.0\n", "lock");
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, QueryCache::key("import cpp\nfrom Function f\nselect f", "db2", "2.15.0", "lock"));
+        assert_ne!(a, QueryCache::key("import cpp\nfrom Function f\nselect f", "db", "2.16.0", "lock"));
+        assert_ne!(a, QueryCache::key("import cpp\nfrom Function f\nselect f", "db", "2.15.0", "lock2"));
+    }
+
+    #[test]
+    fn test_get_put_and_stats() {
+        let dir = test_dir("query_cache_stats");
+        let cache = QueryCache::new(dir.path(), DEFAULT_MAX_BYTES).unwrap();
+        assert_eq!(cache.get("k"), None);
+        cache.put("k", "filename\nsrc/a.c\n").unwrap();
+        assert_eq!(cache.get("k").as_deref(), Some("filename\nsrc/a.c\n"));
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+    }
+
+    #[test]
+    fn test_evicts_oldest_entries() {
+        let dir = test_dir("query_cache_evict");
+        let cache = QueryCache::new(dir.path(), 10).unwrap();
+        cache.put("old", "12345").unwrap();
+        // 수정 시각 해상도가 낮은 파일 시스템 대비
+        std::thread::sleep(Duration::from_millis(20));
+        cache.put("new", "123456").unwrap();
+        assert_eq!(cache.get("old"), None);
+        assert_eq!(cache.get("new").as_deref(), Some("123456"));
+        // 제한보다 큰 결과는 저장하지 않음
+        cache.put("huge", "12345678901").unwrap();
+        assert_eq!(cache.get("huge"), None);
+    }
+}
@@ -0,0 +1,298 @@
+// callgraph.rs - 호출 그래프 탐색 결과
+//
+// 쿼리는 시작 함수에서 depth 단계까지 닿는 호출 간선만 반환하고,
+// 트리 구성과 DOT 출력은 여기서 합니다.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use serde::{Deserialize, Serialize};
+
+/// 호출 그래프에서 허용하는 최대 깊이
+pub const MAX_DEPTH: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallDirection {
+    /// 이 함수를 호출하는 함수들
+    Callers,
+    /// 이 함수가 호출하는 함수들
+    Callees,
+}
+
+/// 쿼리 결과 한 행. depth는 탐색 쪽 끝(callees면 caller, callers면 callee)의 깊이
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallEdge {
+    pub caller: String,
+    pub caller_file: String,
+    pub caller_line: u32,
+    pub callee: String,
+    pub callee_file: String,
+    pub callee_line: u32,
+    // direct, virtual, pointer
+    pub kind: String,
+    pub call_line: u32,
+    pub depth: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct FunctionKey {
+    name: String,
+    filename: String,
+    line: u32,
+}
+
+impl FunctionKey {
+    // 필드를 먼저 이스케이프해야 경로의 역슬래시(Windows)가 줄바꿈 구분자와 섞이지 않음
+    fn dot_id(&self) -> String {
+        format!("{}\\n{}:{}", dot_escape(&self.name), dot_escape(&self.filename), self.line)
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallNode {
+    pub function: String,
+    // 소스코드 루트 밖(시스템 헤더, 라이브러리)이면 빈 문자열
+    pub filename: String,
+    pub line: u32,
+    /// 부모와의 호출 종류. 루트는 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// 호출이 일어난 라인 (caller 파일 기준)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub call_lines: Vec<u32>,
+    /// 트리의 다른 곳에서 이미 펼친 함수 (재귀 포함)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<CallNode>,
+}
+
+// 부모에서 자식으로 가는 간선. 같은 자식으로 가는 호출은 라인만 모음
+#[derive(Debug, Clone)]
+struct Link {
+    child: FunctionKey,
+    kind: String,
+    call_lines: Vec<u32>,
+}
+
+pub struct CallGraph {
+    depth: u32,
+    roots: Vec<FunctionKey>,
+    links: BTreeMap<FunctionKey, Vec<Link>>,
+    // DOT 출력용 (caller, callee, kind)
+    edges: Vec<(FunctionKey, FunctionKey, String)>,
+}
+
+impl CallGraph {
+    pub fn new(edges: &[CallEdge], direction: CallDirection, depth: u32) -> Self {
+        let mut roots = Vec::new();
+        let mut links: BTreeMap<FunctionKey, Vec<Link>> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        let mut dot_edges = Vec::new();
+
+        for edge in edges {
+            let caller = FunctionKey { name: edge.caller.clone(), filename: edge.caller_file.clone(), line: edge.caller_line };
+            let callee = FunctionKey { name: edge.callee.clone(), filename: edge.callee_file.clone(), line: edge.callee_line };
+            let (parent, child) = match direction {
+                CallDirection::Callees => (caller.clone(), callee.clone()),
+                CallDirection::Callers => (callee.clone(), caller.clone()),
+            };
+            if edge.depth == 0 && !roots.contains(&parent) {
+                roots.push(parent.clone());
+            }
+            // 같은 간선이 깊이마다 한 번씩 나올 수 있음
+            if !seen.insert((caller.clone(), callee.clone(), edge.kind.clone(), edge.call_line)) {
+                continue;
+            }
+
+            let children = links.entry(parent).or_default();
+            match children.iter_mut().find(|link| link.child == child && link.kind == edge.kind) {
+                Some(link) => link.call_lines.push(edge.call_line),
+                None => children.push(Link { child, kind: edge.kind.clone(), call_lines: vec![edge.call_line] }),
+            }
+            if !dot_edges.iter().any(|(a, b, kind)| *a == caller && *b == callee && *kind == edge.kind) {
+                dot_edges.push((caller, callee, edge.kind.clone()));
+            }
+        }
+
+        for children in links.values_mut() {
+            for link in children.iter_mut() {
+                link.call_lines.sort_unstable();
+            }
+        }
+        roots.sort();
+
+        CallGraph { depth, roots, links, edges: dot_edges }
+    }
+
+    /// 루트마다 depth 단계까지 펼친 트리
+    ///
+    /// 같은 함수는 가장 얕은 위치에서 한 번만 펼치고 나머지는 repeated로 표시
+    pub fn trees(&self) -> Vec<CallNode> {
+        let min_depth = self.min_depths();
+        let mut expanded = HashSet::new();
+        self.roots
+            .iter()
+            .map(|root| self.node(root, None, Vec::new(), 0, &min_depth, &mut expanded))
+            .collect()
+    }
+
+    fn min_depths(&self) -> HashMap<&FunctionKey, u32> {
+        let mut depths = HashMap::new();
+        let mut queue = VecDeque::new();
+        for root in &self.roots {
+            depths.insert(root, 0);
+            queue.push_back((root, 0));
+        }
+        while let Some((key, depth)) = queue.pop_front() {
+            if depth >= self.depth {
+                continue;
+            }
+            for link in self.links.get(key).into_iter().flatten() {
+                if !depths.contains_key(&link.child) {
+                    depths.insert(&link.child, depth + 1);
+                    queue.push_back((&link.child, depth + 1));
+                }
+            }
+        }
+        depths
+    }
+
+    fn node<'a>(
+        &'a self,
+        key: &'a FunctionKey,
+        kind: Option<&str>,
+        call_lines: Vec<u32>,
+        depth: u32,
+        min_depth: &HashMap<&FunctionKey, u32>,
+        expanded: &mut HashSet<&'a FunctionKey>,
+    ) -> CallNode {
+        let mut node = CallNode {
+            function: key.name.clone(),
+            filename: key.filename.clone(),
+            line: key.line,
+            kind: kind.map(str::to_string),
+            call_lines,
+            repeated: false,
+            children: Vec::new(),
+        };
+        let links = self.links.get(key).map(Vec::as_slice).unwrap_or_default();
+        if links.is_empty() {
+            return node;
+        }
+        if min_depth.get(key) != Some(&depth) || expanded.contains(key) {
+            node.repeated = true;
+            return node;
+        }
+        if depth >= self.depth {
+            return node;
+        }
+        expanded.insert(key);
+        node.children = links
+            .iter()
+            .map(|link| self.node(&link.child, Some(&link.kind), link.call_lines.clone(), depth + 1, min_depth, expanded))
+            .collect();
+        node
+    }
+
+    /// Graphviz DOT. 간선은 방향과 관계없이 caller -> callee
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n    node [shape=box];\n");
+        for root in &self.roots {
+            let _ = writeln!(dot, "    \"{}\" [style=bold];", root.dot_id());
+        }
+        for (caller, callee, kind) in &self.edges {
+            let style = match kind.as_str() {
+                "direct" => String::new(),
+                kind => format!(" [style=dashed, label=\"{}\"]", kind),
+            };
+            let _ = writeln!(dot, "    \"{}\" -> \"{}\"{};", caller.dot_id(), callee.dot_id(), style);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(caller: &str, callee: &str, kind: &str, call_line: u32, depth: u32) -> CallEdge {
+        CallEdge {
+            caller: caller.to_string(),
+            caller_file: "lib/a.c".to_string(),
+            caller_line: caller.len() as u32,
+            callee: callee.to_string(),
+            callee_file: "lib/a.c".to_string(),
+            callee_line: callee.len() as u32,
+            kind: kind.to_string(),
+            call_line,
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_callee_tree() {
+        let edges = vec![
+            edge("main", "parse", "direct", 10, 0),
+            edge("main", "parse", "direct", 12, 0),
+            edge("main", "run", "direct", 11, 0),
+            edge("run", "handler", "pointer", 20, 1),
+            edge("run", "parse", "direct", 21, 1),
+            edge("handler", "run", "direct", 30, 2),
+            // parse는 깊이 1과 2에서 모두 닿음
+            edge("parse", "strlen", "direct", 40, 1),
+            edge("parse", "strlen", "direct", 40, 2),
+        ];
+        let graph = CallGraph::new(&edges, CallDirection::Callees, 3);
+        let trees = graph.trees();
+        assert_eq!(trees.len(), 1);
+        let main = &trees[0];
+        assert_eq!((main.function.as_str(), main.kind.as_deref()), ("main", None));
+
+        let parse = &main.children[0];
+        assert_eq!(parse.call_lines, vec![10, 12]);
+        assert_eq!(parse.children[0].function, "strlen");
+
+        let run = &main.children[1];
+        let handler = &run.children[0];
+        assert_eq!(handler.kind.as_deref(), Some("pointer"));
+        // 재귀와 이미 펼친 함수는 다시 펼치지 않음
+        assert!(handler.children[0].repeated);
+        assert!(run.children[1].repeated && run.children[1].children.is_empty());
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"main\\nlib/a.c:4\" [style=bold];"));
+        assert!(dot.contains("\"run\\nlib/a.c:3\" -> \"handler\\nlib/a.c:7\" [style=dashed, label=\"pointer\"];"));
+        assert_eq!(dot.matches("\"main\\nlib/a.c:4\" -> \"parse").count(), 1);
+    }
+
+    #[test]
+    fn test_caller_tree_is_reversed() {
+        let edges = vec![
+            edge("do_read", "recv", "direct", 5, 0),
+            edge("poll", "do_read", "virtual", 8, 1),
+        ];
+        let graph = CallGraph::new(&edges, CallDirection::Callers, 2);
+        let trees = graph.trees();
+        assert_eq!(trees[0].function, "recv");
+        assert_eq!(trees[0].children[0].function, "do_read");
+        assert_eq!(trees[0].children[0].children[0].kind.as_deref(), Some("virtual"));
+        // DOT 간선은 호출 방향 그대로
+        assert!(graph.to_dot().contains("\"do_read\\nlib/a.c:7\" -> \"recv\\nlib/a.c:4\";"));
+    }
+
+    #[test]
+    fn test_dot_id_escapes_fields() {
+        let key = FunctionKey {
+            name: "operator\"\"_x".to_string(),
+            filename: "src\\new\\a.cpp".to_string(),
+            line: 3,
+        };
+        assert_eq!(key.dot_id(), r#"operator\"\"_x\nsrc\\new\\a.cpp:3"#);
+    }
+}
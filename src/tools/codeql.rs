@@ -1,123 +1,516 @@
 // codeql.rs
 
-use std::process::Command;
+use std::process::{Command, Output};
 use crate::error::{AppError, Result};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use std::fs;
 use serde::{Serialize, Deserialize};
 use crate::utils::utils::parse_csv;
 use crate::tools::filesystem::FileSystem;
+use crate::tools::language::Language;
+use crate::tools::bqrs::BqrsResults;
+use crate::tools::cache::{CacheStats, QueryCache};
+use crate::tools::callgraph::{CallDirection, CallEdge, CallGraph};
+use crate::tools::queries::{self, FlowSpec};
+use crate::tools::pack::{PackQuery, QueryPacks};
+use crate::tools::query_server::QueryServer;
+use crate::tools::sarif::{Finding, SarifLog};
+use crate::tools::symbols::{self, Symbol, SymbolKind, SymbolPattern};
+use crate::tools::workspace::{WorkDir, Workspace};
+
+// 에러 메시지에 남길 stderr 줄 수
+pub(crate) const STDERR_TAIL_LINES: usize = 20;
+
+// 모델이 요청한 쿼리 하나가 이보다 오래 걸리면 중단
+const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// bqrs 결과를 읽는 형식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeFormat {
+    Csv,
+    Json,
+    /// `@kind path-problem` 쿼리 결과를 `codeql bqrs interpret`로 SARIF로 변환
+    PathSarif,
+}
+
+impl DecodeFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecodeFormat::Csv => "csv",
+            DecodeFormat::Json => "json",
+            DecodeFormat::PathSarif => "sarif",
+        }
+    }
+}
+
+// path-problem 결과를 해석할 때 쓰는 쿼리 id
+const PATH_QUERY_ID: &str = "codeql-agent/path-query";
+
+/// 컴파일 언어 추출 방식 (`--build-mode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildMode {
+    None,
+    Autobuild,
+    Manual,
+}
+
+impl BuildMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuildMode::None => "none",
+            BuildMode::Autobuild => "autobuild",
+            BuildMode::Manual => "manual",
+        }
+    }
+}
+
+/// `codeql database create` 추가 옵션
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseOptions {
+    /// 빌드 명령 (`--command`), C/C++ 같은 컴파일 언어에서 사용
+    pub command: Option<String>,
+    /// 이미 있는 데이터베이스를 덮어씀 (`--overwrite`)
+    pub overwrite: bool,
+    /// 추출에 쓸 스레드 수 (`--threads`), 0이면 코어 수만큼
+    pub threads: Option<u32>,
+    pub build_mode: Option<BuildMode>,
+    /// 추출 전체 제한 시간, None이면 제한 없음
+    pub timeout: Option<Duration>,
+}
+
+impl DatabaseOptions {
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(command) = &self.command {
+            args.push(format!("--command={}", command));
+        }
+        if self.overwrite {
+            args.push("--overwrite".to_string());
+        }
+        if let Some(threads) = self.threads {
+            args.push(format!("--threads={}", threads));
+        }
+        if let Some(build_mode) = self.build_mode {
+            args.push(format!("--build-mode={}", build_mode.as_str()));
+        }
+        args
+    }
+}
+
+// 명령을 실행하고 출력을 모음. 제한 시간이 지나면 future를 버리면서 kill_on_drop으로 프로세스도 종료
+pub(crate) async fn output_with_timeout(
+    mut command: tokio::process::Command,
+    operation: &str,
+    timeout: Option<Duration>,
+) -> Result<Output> {
+    command.kill_on_drop(true);
+    let output = command.output();
+    match timeout {
+        Some(limit) => tokio::time::timeout(limit, output)
+            .await
+            .map_err(|_| AppError::Timeout {
+                operation: operation.to_string(),
+                timeout: limit,
+            })?
+            .map_err(AppError::from),
+        None => Ok(output.await?),
+    }
+}
+
+// 작업 디렉터리를 남기는 설정이면 CodeQL 에러에 경로를 덧붙임
+fn with_artifacts_hint(error: AppError, work_dir: &WorkDir) -> AppError {
+    match error {
+        AppError::CodeQLError(message) if work_dir.kept_on_failure() => AppError::CodeQLError(format!(
+            "{}\n(query artifacts kept in {})",
+            message.trim_end(),
+            work_dir.path().display()
+        )),
+        other => other,
+    }
+}
+
+// 긴 추출 로그에서 마지막 몇 줄만 남김
+pub(crate) fn stderr_tail(stderr: &str, lines: usize) -> String {
+    let all: Vec<&str> = stderr.trim_end().lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
 
 pub struct CodeQLRunner {
     db_path: String,
     src_path: String,
+    // 데이터베이스가 아직 없으면 비어 있다가 처음 읽을 때 채워짐
+    language: OnceLock<Language>,
+    query_timeout: Option<Duration>,
+    // 세션 동안 재사용하는 query-server2. 처음 쿼리를 실행할 때 띄움
+    query_server: tokio::sync::Mutex<Option<QueryServer>>,
+    // 서버를 띄울 수 없으면 꺼서 이후에는 바로 CLI로 실행
+    use_query_server: AtomicBool,
+    // 캐시 키에 들어가는 `codeql --version` 출력
+    codeql_version: String,
+    cache: Option<QueryCache>,
+    workspace: Workspace,
+    packs: QueryPacks,
 }
 
 impl CodeQLRunner {
     pub fn new(src_path: impl Into<String>, db_path: impl Into<String>) -> Result<Self> {
-        Command::new("codeql")
+        let version = Command::new("codeql")
             .arg("--version")
             .output()?;
+        let codeql_version = String::from_utf8_lossy(&version.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        let db_path = db_path.into();
+        let language = OnceLock::new();
+        match Language::from_database(&db_path) {
+            Ok(detected) => {
+                let _ = language.set(detected);
+            }
+            // create_database로 나중에 만들 수 있으므로 데이터베이스가 없는 건 허용
+            Err(AppError::IoError(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
         
         Ok(CodeQLRunner {
             src_path: src_path.into(),
-            db_path: db_path.into(),
+            db_path,
+            language,
+            query_timeout: Some(DEFAULT_QUERY_TIMEOUT),
+            query_server: tokio::sync::Mutex::new(None),
+            use_query_server: AtomicBool::new(true),
+            codeql_version,
+            cache: None,
+            workspace: Workspace::default(),
+            packs: QueryPacks::default(),
         })
     }
+
+    /// 쿼리 팩 위치, 오프라인 여부, 추가 팩 경로
+    pub fn with_packs(mut self, packs: QueryPacks) -> Self {
+        self.packs = packs;
+        self
+    }
+
+    /// 쿼리 작업 디렉터리를 만들 위치와 보존 정책
+    pub fn with_workspace(mut self, workspace: Workspace) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// 쿼리 결과를 디스크 캐시에 저장하고 같은 쿼리는 다시 실행하지 않음
+    pub fn with_cache(mut self, cache: QueryCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 캐시를 쓰지 않으면 None
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(QueryCache::stats)
+    }
+
+    /// false면 query-server2 없이 쿼리마다 `codeql query run` 실행
+    pub fn with_query_server(self, enabled: bool) -> Self {
+        self.use_query_server.store(enabled, Ordering::Relaxed);
+        self
+    }
+
+    /// 쿼리 하나(`query run`, `bqrs decode` 각각)의 제한 시간. None이면 제한 없음
+    pub fn with_query_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.query_timeout = timeout;
+        self
+    }
     
+    /// `codeql database create` 실행. 실패하면 종료 코드, stderr 끝부분, 로그 디렉터리를 담은 에러 반환
     pub async fn create_database(
-        &self, 
+        &self,
         source_path: &str,
-        language: &str
+        language: Language,
+        options: &DatabaseOptions,
     ) -> Result<()> {
 
-        Command::new("codeql")
+        let mut command = tokio::process::Command::new("codeql");
+        command
             .arg("database")
             .arg("create")
             .arg(format!("--language={}", language))
             .arg(format!("--source-root={}", source_path))
-            .arg(&self.db_path)
-            .output()?;
+            .args(options.args())
+            .arg(&self.db_path);
+        let output = output_with_timeout(command, "codeql database create", options.timeout).await?;
+
+        if !output.status.success() {
+            return Err(AppError::DatabaseCreateError {
+                exit_code: output.status.code(),
+                stderr: stderr_tail(&String::from_utf8_lossy(&output.stderr), STDERR_TAIL_LINES),
+                log_dir: PathBuf::from(&self.db_path).join("log"),
+            });
+        }
+
+        // 데이터베이스가 없던 상태에서 만들었으면 언어를 바로 기억
+        let _ = self.language.set(language);
         Ok(())
     }
 
+    /// 쿼리를 실행해서 `#select` 결과를 CSV로 반환 (parse_csv로 읽는 간단한 쿼리용)
     pub async fn run_query(
         &self,
         query_string: &str
     ) -> Result<String> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis();
-        
-        let work_dir = PathBuf::from("tmp").join(format!("query_{}", timestamp));
-        fs::create_dir_all(&work_dir)?;
-        
-        let language = self.detect_language(query_string);
-        
-        let qlpack_path = work_dir.join("qlpack.yml");
-        let qlpack_content = format!(r#"name: tmpql-{}
-version: 0.0.1
-libraryPathDependencies:
-  - codeql/{}-all
-"#, timestamp, language);
-        fs::write(&qlpack_path, qlpack_content)?;
-
-        let query_path = work_dir.join("query.ql");
+        self.run_decoded(query_string, DecodeFormat::Csv).await
+    }
+
+    /// `@kind path-problem` 쿼리를 실행해서 경고마다 source에서 sink까지의 경로가 담긴 SARIF 반환
+    ///
+    /// 쿼리는 `edges` 쿼리 술어와 `select sink, source, sink, message` 형태의 select가 있어야 함
+    pub async fn run_path_query(&self, query_string: &str) -> Result<SarifLog> {
+        SarifLog::parse(&self.run_decoded(query_string, DecodeFormat::PathSarif).await?)
+    }
+
+    /// 쿼리를 실행해서 모든 결과 집합을 컬럼 타입, 엔티티 위치와 함께 반환
+    pub async fn run_query_json(&self, query_string: &str) -> Result<BqrsResults> {
+        BqrsResults::parse(&self.run_decoded(query_string, DecodeFormat::Json).await?)
+    }
+
+    async fn run_decoded(&self, query_string: &str, format: DecodeFormat) -> Result<String> {
+        let language = self.language()?;
+        if let Some(imported) = Language::from_query(query_string) {
+            if imported != language {
+                return Err(AppError::LanguageMismatch {
+                    database: language,
+                    query: imported,
+                });
+            }
+        }
+
+        let cache_key = match &self.cache {
+            Some(cache) => {
+                let pack_lock = self.packs.lock_contents(language).await?;
+                let key = format!(
+                    "{}.{}",
+                    QueryCache::key(query_string, &self.db_fingerprint()?, &self.codeql_version, &pack_lock),
+                    format.as_str()
+                );
+                if let Some(cached) = cache.get(&key) {
+                    return Ok(cached);
+                }
+                Some(key)
+            }
+            None => None,
+        };
+
+        let mut work_dir = self.workspace.create("query")?;
+        let decoded = self.run_in(&work_dir, language, query_string, format).await
+            .map_err(|e| with_artifacts_hint(e, &work_dir))?;
+        work_dir.succeed();
+
+        if let (Some(cache), Some(key)) = (&self.cache, cache_key) {
+            // 캐시 저장 실패는 쿼리 결과에 영향 없음
+            let _ = cache.put(&key, &decoded);
+        }
+        Ok(decoded)
+    }
+
+    /// 쿼리 스위트(`cpp-security-extended.qls`, `codeql/cpp-queries:codeql-suites/...` 등)를 실행해서 SARIF 반환
+    ///
+    /// 스위트 하나는 쿼리 수백 개라 쿼리 제한 시간을 적용하지 않음
+    pub async fn analyze(&self, suite: &str) -> Result<SarifLog> {
+        let mut work_dir = self.workspace.create("analyze")?;
+        let sarif_path = work_dir.join("results.sarif");
+
+        let mut command = tokio::process::Command::new("codeql");
+        command
+            .arg("database")
+            .arg("analyze")
+            .arg(&self.db_path)
+            .arg(suite)
+            .arg("--format=sarif-latest")
+            .arg(format!("--output={}", sarif_path.display()));
+        if !self.packs.offline() {
+            // 스위트가 들어 있는 쿼리 팩이 없으면 내려받음
+            command.arg("--download");
+        }
+        if let Some(packs) = self.packs.additional_packs_arg() {
+            command.arg(packs);
+        }
+        let output = output_with_timeout(command, "codeql database analyze", None).await?;
+
+        if !output.status.success() {
+            let _ = fs::write(work_dir.join("analyze.log"), &output.stderr);
+            let error = AppError::CodeQLError(stderr_tail(&String::from_utf8_lossy(&output.stderr), STDERR_TAIL_LINES));
+            return Err(with_artifacts_hint(error, &work_dir));
+        }
+
+        let sarif = SarifLog::from_file(&sarif_path)?;
+        work_dir.succeed();
+        Ok(sarif)
+    }
+
+    // 언어 팩에 쿼리를 쓰고 실행해서 디코딩한 결과를 읽음
+    async fn run_in(&self, work_dir: &WorkDir, language: Language, query_string: &str, format: DecodeFormat) -> Result<String> {
+        // 쿼리는 언어 팩 안에 있어야 팩의 lockfile로 라이브러리를 찾음
+        let pack_dir = self.packs.ensure(language).await?;
+        let query = PackQuery::write(&pack_dir, &work_dir.name(), query_string)?;
+        // 디버깅용으로 작업 디렉터리에도 남김
+        fs::write(work_dir.join("query.ql"), query_string)?;
+
         let bqrs_path = work_dir.join("result.bqrs");
-        let csv_path = work_dir.join("result.csv");
-        
-        fs::write(&query_path, query_string)?;
-        
-        let output = Command::new("codeql")
+        let decoded_path = work_dir.join(&format!("result.{}", format.as_str()));
+
+        self.evaluate(query.path(), &bqrs_path).await?;
+
+        let mut command = tokio::process::Command::new("codeql");
+        match format {
+            DecodeFormat::Csv | DecodeFormat::Json => {
+                command
+                    .arg("bqrs")
+                    .arg("decode")
+                    .arg(format!("--format={}", format.as_str()));
+            }
+            DecodeFormat::PathSarif => {
+                command
+                    .arg("bqrs")
+                    .arg("interpret")
+                    .arg("--format=sarif-latest")
+                    .arg("--metadata=kind=path-problem")
+                    .arg(format!("--metadata=id={}", PATH_QUERY_ID))
+                    .arg(format!("--source-location-prefix={}", self.source_location_prefix()?));
+            }
+        }
+        command
+            .arg(format!("--output={}", decoded_path.display()))
+            .arg(&bqrs_path);
+        let output = output_with_timeout(command, "codeql bqrs decode", self.query_timeout).await?;
+
+        if !output.status.success() {
+            let _ = fs::write(work_dir.join("decode.log"), &output.stderr);
+            return Err(AppError::CodeQLError(String::from_utf8_lossy(&output.stderr).to_string()));
+        }
+
+        Ok(fs::read_to_string(&decoded_path)?)
+    }
+
+    // SARIF 경로를 소스 루트 기준 상대 경로로 만들기 위한 codeql-database.yml의 sourceLocationPrefix
+    fn source_location_prefix(&self) -> Result<String> {
+        let metadata = fs::read_to_string(PathBuf::from(&self.db_path).join("codeql-database.yml"))?;
+        Ok(metadata
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("sourceLocationPrefix:"))
+            .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+            .unwrap_or_else(|| self.src_path.clone()))
+    }
+
+    // 데이터베이스 절대 경로와 codeql-database.yml 내용 (다시 만들면 creationTime이 바뀜)
+    fn db_fingerprint(&self) -> Result<String> {
+        let db_path = std::path::absolute(&self.db_path)?;
+        let metadata = fs::read_to_string(db_path.join("codeql-database.yml"))?;
+        Ok(format!("{}\n{}", db_path.display(), metadata))
+    }
+    
+    // 쿼리를 실행해서 bqrs를 만듦. query-server2를 먼저 쓰고, 통신이 안 되면 `codeql query run`으로 실행
+    async fn evaluate(&self, query_path: &Path, bqrs_path: &Path) -> Result<()> {
+        // CLI도 실패하면 서버를 못 쓴 이유를 같이 알려줌
+        let mut server_error = None;
+        if self.use_query_server.load(Ordering::Relaxed) {
+            match self.evaluate_with_server(query_path, bqrs_path).await {
+                Ok(result) => return result,
+                Err(reason) => server_error = Some(reason),
+            }
+        }
+        let fallback_error = |message: String| match &server_error {
+            Some(reason) => AppError::CodeQLError(format!("{}\n(query-server2 was not used: {})", message, reason)),
+            None => AppError::CodeQLError(message),
+        };
+
+        let mut command = tokio::process::Command::new("codeql");
+        command
             .arg("query")
             .arg("run")
-            .arg(&query_path)
+            .arg(query_path)
             .arg(format!("--database={}", &self.db_path))
-            .arg(format!("--output={}", bqrs_path.display()))
-            .output()?;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            let _ = fs::remove_dir_all(&work_dir);
-            return Err(AppError::CodeQLError(stderr));
+            .arg(format!("--output={}", bqrs_path.display()));
+        if let Some(packs) = self.packs.additional_packs_arg() {
+            command.arg(packs);
         }
-        
-        let output = Command::new("codeql")
-            .arg("bqrs")
-            .arg("decode")
-            .arg(&bqrs_path)
-            .arg("--format=csv")
-            .arg(format!("--output={}", csv_path.display()))
-            .output()?;
-        
+        let output = match output_with_timeout(command, "codeql query run", self.query_timeout).await {
+            Ok(output) => output,
+            Err(AppError::IoError(e)) => return Err(fallback_error(e.to_string())),
+            Err(e) => return Err(e),
+        };
+
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            let _ = fs::remove_dir_all(&work_dir);
-            return Err(AppError::CodeQLError(stderr));
+            if let Some(work_dir) = bqrs_path.parent() {
+                let _ = fs::write(work_dir.join("query.log"), &output.stderr);
+            }
+            return Err(fallback_error(String::from_utf8_lossy(&output.stderr).to_string()));
         }
-        
-        let csv_content = fs::read_to_string(&csv_path)?;
-        
-        let _ = fs::remove_dir_all(&work_dir);
-        Ok(csv_content)
+        Ok(())
     }
-    
-    fn detect_language(&self, query_string: &str) -> &str {
-        if query_string.contains("import cpp") {
-            "cpp"
-        } else if query_string.contains("import python") {
-            "python"
-        } else if query_string.contains("import java") {
-            "java"
-        } else if query_string.contains("import javascript") {
-            "javascript"
-        } else if query_string.contains("import csharp") {
-            "csharp"
-        } else {
-            "cpp"
+
+    // Err이면 서버를 쓸 수 없는 상태라 CLI로 다시 실행해야 함 (Err는 그 이유)
+    async fn evaluate_with_server(&self, query_path: &Path, bqrs_path: &Path) -> std::result::Result<Result<()>, String> {
+        let db_path = match std::path::absolute(&self.db_path) {
+            Ok(path) => path,
+            Err(e) => return Ok(Err(e.into())),
+        };
+        let mut server = self.query_server.lock().await;
+
+        if server.is_none() {
+            match QueryServer::start(&db_path, self.query_timeout).await {
+                Ok(started) => *server = Some(started),
+                Err(e) => {
+                    self.use_query_server.store(false, Ordering::Relaxed);
+                    return Err(format!("cannot start query-server2: {}", e));
+                }
+            }
+        }
+
+        let paths = [query_path, bqrs_path].map(std::path::absolute);
+        let [query_path, bqrs_path] = match paths {
+            [Ok(a), Ok(b)] => [a, b],
+            _ => return Ok(Err(AppError::CodeQLError("Cannot resolve query paths".to_string()))),
+        };
+        let additional_packs: Vec<&Path> = self.packs.additional_packs().iter().map(PathBuf::as_path).collect();
+        let Some(started) = server.as_mut() else {
+            return Err("query-server2 is not running".to_string());
+        };
+        let running = started.run_query(&db_path, &query_path, &bqrs_path, &additional_packs);
+        let finished = match self.query_timeout {
+            Some(limit) => tokio::time::timeout(limit, running).await.ok(),
+            None => Some(running.await),
+        };
+        let Some(result) = finished else {
+            // 평가 중인 서버는 재사용할 수 없으니 종료하고 다음 쿼리에서 새로 띄움
+            *server = None;
+            return Ok(Err(AppError::Timeout {
+                operation: "query-server2 evaluation/runQuery".to_string(),
+                timeout: self.query_timeout.unwrap_or_default(),
+            }));
+        };
+
+        match result {
+            Ok(result) => Ok(result.into_result()),
+            Err(e) => {
+                *server = None;
+                Err(format!("query-server2 communication failed: {}", e))
+            }
         }
     }
+
+    /// codeql-database.yml의 primaryLanguage
+    pub fn language(&self) -> Result<Language> {
+        if let Some(language) = self.language.get() {
+            return Ok(*language);
+        }
+        let language = Language::from_database(&self.db_path)?;
+        Ok(*self.language.get_or_init(|| language))
+    }
 }
 
 // #[tokio::test]
@@ -152,17 +545,30 @@ libraryPathDependencies:
 // 소스코드와 라인 정보를 반환하는 녀석
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct SourceInfoParse {
-    pub filename: String,
-    pub startline: u32,
-    pub endline: u32,
+struct VarDefinitionParse {
+    filename: String,
+    startline: u32,
+    endline: u32,
+    column: u32,
+    kind: String,
+    scope: String,
+    shadows: String,  // "; "로 구분, 없으면 빈 문자열
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct SourceInfoResult {
+pub struct VarDefinitionResult {
     pub filename: String,
     pub line: u32,
-    pub code: String
+    pub code: String,
+    // local, parameter, global, member 등
+    pub kind: String,
+    // 변수가 속한 함수나 클래스. 전역이면 빈 문자열
+    pub scope: String,
+    // 이 변수가 가리는 바깥 변수들
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub shadows: Vec<String>,
+    // 후보를 고른 접근의 열. 다시 찾을 때 column으로 넘기면 됨
+    pub column: u32,
 }
 
 pub struct CodeQLAnalyzer {
@@ -198,6 +604,599 @@ struct FunctionInfoResult {
     overrides: Option<Vec<FunctionInfoResult>>,
 }
 
+// find_function_implementation 결과 한 건. 호출한 것이 매크로일 수 있어서 kind로 구분
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ImplementationResult {
+    Function(FunctionInfoResult),
+    Macro(MacroResult),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FunctionRefParse {
+    caller: String,
+    filename: String,
+    line: u32,
+    startline: u32,
+    endline: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FunctionRefResult {
+    pub caller: String,
+    pub filename: String,
+    pub line: u32,
+    pub code: String,
+    // 이 함수 안에서 대상 함수를 호출한 라인들
+    pub call_lines: Vec<u32>,
+    #[serde(skip)]
+    endline: u32,
+}
+
+// 같은 함수 안의 여러 호출은 한 결과로 묶어서 코드를 한 번만 읽게 함. code는 비워 둠
+fn function_refs_from_rows(mut rows: Vec<FunctionRefParse>) -> Vec<FunctionRefResult> {
+    rows.sort_by(|a, b| (&a.filename, a.startline, a.line).cmp(&(&b.filename, b.startline, b.line)));
+    let mut results: Vec<FunctionRefResult> = Vec::new();
+    for call in rows {
+        if let Some(last) = results.last_mut() {
+            if last.filename == call.filename && last.caller == call.caller {
+                if !last.call_lines.contains(&call.line) {
+                    last.call_lines.push(call.line);
+                }
+                continue;
+            }
+        }
+        results.push(FunctionRefResult {
+            caller: call.caller,
+            filename: call.filename,
+            line: call.startline,
+            code: String::new(),
+            call_lines: vec![call.line],
+            endline: call.endline,
+        });
+    }
+    results
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FunctionCodeParse {
+    qualified_name: String,
+    signature: String,
+    filename: String,
+    startline: u32,
+    endline: u32,
+    is_static: String,  // "true" 또는 "false" 문자열로 옴
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FunctionCodeResult {
+    pub qualified_name: String,
+    pub signature: String,
+    pub filename: String,
+    pub line: u32,
+    pub code: String,
+    pub is_static: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VarRefParse {
+    filename: String,
+    line: u32,
+    column: u32,
+    kind: String,
+    function: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VarRefResult {
+    pub filename: String,
+    pub line: u32,
+    pub column: u32,
+    // read, write, address, argument 중 하나 이상
+    pub kinds: Vec<String>,
+    pub function: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TypeRowParse {
+    section: String,
+    owner: String,
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    filename: String,
+    startline: u32,
+    endline: u32,
+    offset: i64,  // 모르면 -1
+    size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypedefInfo {
+    pub name: String,
+    // typedef가 가리키는 타입
+    pub aliased: String,
+    pub filename: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypeMember {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub line: u32,
+    pub offset: Option<u64>,
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypeBase {
+    pub name: String,
+    pub filename: String,
+    pub line: u32,
+    pub is_virtual: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypeDefinition {
+    // struct, class, union, enum, interface
+    pub kind: String,
+    pub name: String,
+    pub filename: String,
+    pub line: u32,
+    #[serde(skip)]
+    endline: u32,
+    pub size: Option<u64>,
+    // 소스코드 루트 밖(시스템 헤더 등)이면 None
+    pub code: Option<String>,
+    pub members: Vec<TypeMember>,
+    pub bases: Vec<TypeBase>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypeDefinitionResult {
+    /// 사용한 이름에서 정의까지 따라간 typedef 순서
+    pub typedefs: Vec<TypedefInfo>,
+    pub definitions: Vec<TypeDefinition>,
+    /// 개수 제한으로 빠진 정의 수
+    #[serde(default)]
+    pub omitted: usize,
+}
+
+// find_type_definition이 반환하는 최대 정의 수
+const MAX_TYPE_DEFINITIONS: usize = 5;
+
+impl TypeDefinitionResult {
+    /// 요청한 파일의 정의, 멤버나 베이스가 있는 정의 순으로 정렬하고 limit개만 남김
+    ///
+    /// 위치에서 타입을 못 찾으면 같은 이름의 타입이 모두 나오기 때문 (번역 단위마다 다른 정의 등)
+    fn rank_definitions(&mut self, filename: &str, limit: usize) {
+        self.definitions.sort_by_key(|d| {
+            (d.filename != filename, d.members.is_empty() && d.bases.is_empty(), d.filename.clone(), d.line)
+        });
+        self.omitted = self.definitions.len().saturating_sub(limit);
+        self.definitions.truncate(limit);
+    }
+}
+
+fn known(value: i64) -> Option<u64> {
+    u64::try_from(value).ok()
+}
+
+// 쿼리 행을 정의별로 묶음. code는 비워 둠
+fn type_definition_from_rows(mut rows: Vec<TypeRowParse>) -> TypeDefinitionResult {
+    // 멤버는 선언 순서(= C 구조체의 배치 순서)
+    rows.sort_by(|a, b| (&a.section, &a.owner, a.startline).cmp(&(&b.section, &b.owner, b.startline)));
+    rows.dedup_by(|a, b| a.section == b.section && a.owner == b.owner && a.name == b.name && a.startline == b.startline);
+
+    let mut typedefs: Vec<(u32, TypedefInfo)> = Vec::new();
+    let mut definitions: Vec<TypeDefinition> = Vec::new();
+    let mut members = Vec::new();
+    let mut bases = Vec::new();
+    for row in rows {
+        match row.section.as_str() {
+            "typedef" => typedefs.push((
+                row.owner.parse().unwrap_or(0),
+                TypedefInfo { name: row.name, aliased: row.type_name, filename: row.filename, line: row.startline },
+            )),
+            "definition" => definitions.push(TypeDefinition {
+                kind: row.type_name,
+                name: row.name,
+                filename: row.filename,
+                line: row.startline,
+                endline: row.endline,
+                size: known(row.size),
+                code: None,
+                members: Vec::new(),
+                bases: Vec::new(),
+            }),
+            "member" => members.push(row),
+            "base" => bases.push(row),
+            _ => {}
+        }
+    }
+    typedefs.sort_by_key(|(step, _)| *step);
+
+    for definition in &mut definitions {
+        // 번역 단위마다 같은 이름의 정의가 따로 있을 수 있어서 위치로도 맞춰 봄
+        definition.members = members
+            .iter()
+            .filter(|row| {
+                row.owner == definition.name
+                    && row.filename == definition.filename
+                    && (definition.line..=definition.endline).contains(&row.startline)
+            })
+            .map(|row| TypeMember {
+                name: row.name.clone(),
+                type_name: row.type_name.clone(),
+                line: row.startline,
+                offset: known(row.offset),
+                size: known(row.size),
+            })
+            .collect();
+        definition.bases = bases
+            .iter()
+            .filter(|row| row.owner == definition.name)
+            .map(|row| TypeBase {
+                name: row.name.clone(),
+                filename: row.filename.clone(),
+                line: row.startline,
+                is_virtual: row.type_name == "virtual",
+            })
+            .collect();
+    }
+
+    TypeDefinitionResult {
+        typedefs: typedefs.into_iter().map(|(_, typedef)| typedef).collect(),
+        definitions,
+        omitted: 0,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MacroParse {
+    column: u32,
+    head: String,
+    body: String,
+    filename: String,
+    startline: u32,
+    endline: u32,
+    index: i32,  // 인자가 없으면 -1
+    argument: String,
+    expanded: String,
+    expression: String,
+    expression_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MacroResult {
+    pub name: String,
+    /// `#define` 뒤의 이름과 파라미터 목록
+    pub head: String,
+    pub body: String,
+    pub filename: String,
+    pub line: u32,
+    // 정의 코드. 시스템 헤더처럼 소스코드 루트 밖이면 None
+    pub code: Option<String>,
+    // 같은 라인에 여러 번 쓰였으면 column으로 구분
+    pub column: u32,
+    pub arguments: Vec<String>,
+    /// CodeQL이 기록한 인자별 전개 결과
+    pub expanded_arguments: Vec<String>,
+    /// CodeQL이 기록한, 호출이 전개된 최상위 식과 그 타입. 문장이나 선언으로 전개되면 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expression_type: Option<String>,
+    /// body에 인자를 대입한 텍스트. CodeQL은 호출 전체의 전개 텍스트를 기록하지 않아서
+    /// 여기서 만든 근사치이고, body 안의 다른 매크로는 다시 펼치지 않음
+    pub expansion: String,
+    /// expansion이 근사치임을 표시 (항상 true)
+    pub approximate: bool,
+}
+
+fn macro_name(head: &str) -> &str {
+    let end = head.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(head.len());
+    &head[..end]
+}
+
+// 함수형 매크로의 파라미터. 객체형 매크로면 None
+fn macro_params(head: &str) -> Option<Vec<String>> {
+    let rest = head[macro_name(head).len()..].strip_prefix('(')?;
+    let inner = rest.rsplit_once(')').map(|(inner, _)| inner).unwrap_or(rest);
+    if inner.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    Some(inner.split(',').map(|param| param.trim().to_string()).collect())
+}
+
+fn stringify(argument: &str) -> String {
+    format!("\"{}\"", argument.trim().replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// 파라미터 이름별 인자. `...`는 __VA_ARGS__, `args...`는 args로 나머지 인자 전부
+fn macro_values(params: &[String], arguments: &[String]) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    for (i, param) in params.iter().enumerate() {
+        if let Some(named) = param.strip_suffix("...") {
+            let name = if named.trim().is_empty() { "__VA_ARGS__" } else { named.trim() };
+            let rest: Vec<&str> = arguments.iter().skip(i).map(|arg| arg.trim()).collect();
+            values.insert(name.to_string(), rest.join(", "));
+            break;
+        }
+        let argument = arguments.get(i).map(|arg| arg.trim()).unwrap_or_default();
+        values.insert(param.clone(), argument.to_string());
+    }
+    values
+}
+
+/// 매크로 body의 파라미터를 인자로 치환. `#x`는 문자열로, `a ## b`는 붙여서 씀
+///
+/// 전처리기처럼 `#`, `##`의 피연산자는 원래 인자를, 나머지는 전개된 인자(expanded)를 씀
+fn expand_macro(head: &str, body: &str, arguments: &[String], expanded: &[String]) -> String {
+    let Some(params) = macro_params(head) else {
+        return body.trim().to_string();
+    };
+    let raw_values = macro_values(&params, arguments);
+    let values = macro_values(&params, expanded);
+
+    let chars: Vec<char> = body.trim().chars().collect();
+    let mut out = String::new();
+    // 바로 앞이 `##`였는지 (붙일 피연산자는 전개하지 않은 인자를 씀)
+    let mut after_paste = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' || c == '\'' {
+            // 문자열, 문자 리터럴은 그대로
+            out.push(c);
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    out.push(chars[i]);
+                    i += 1;
+                }
+                out.push(chars[i]);
+                i += 1;
+            }
+            if i < chars.len() {
+                out.push(chars[i]);
+                i += 1;
+            }
+        } else if c == '#' && chars.get(i + 1) == Some(&'#') {
+            // 토큰 붙이기: 앞뒤 공백 제거
+            while out.ends_with(char::is_whitespace) {
+                out.pop();
+            }
+            i += 2;
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            after_paste = true;
+            continue;
+        } else if c == '#' {
+            let mut j = i + 1;
+            while j < chars.len() && chars[j].is_whitespace() {
+                j += 1;
+            }
+            let start = j;
+            while j < chars.len() && (chars[j].is_ascii_alphanumeric() || chars[j] == '_') {
+                j += 1;
+            }
+            let ident: String = chars[start..j].iter().collect();
+            match raw_values.get(&ident) {
+                Some(value) => {
+                    out.push_str(&stringify(value));
+                    i = j;
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            let rest = chars[i..].iter().skip_while(|c| c.is_whitespace());
+            let pasted = after_paste || rest.take(2).eq(['#', '#'].iter());
+            let values = if pasted { &raw_values } else { &values };
+            out.push_str(values.get(&ident).map(String::as_str).unwrap_or(&ident));
+        } else if c.is_ascii_digit() {
+            // 1UL 같은 숫자 접미사를 식별자로 보지 않도록 숫자는 통째로
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                out.push(chars[i]);
+                i += 1;
+            }
+        } else {
+            out.push(c);
+            i += 1;
+        }
+        after_paste = false;
+    }
+    out
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SummaryRowParse {
+    filename: String,
+    startline: u32,
+    section: String,
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    line: u32,
+    endline: u32,
+    detail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SummaryVariable {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub line: u32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_static: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SummaryCall {
+    pub function: String,
+    pub lines: Vec<u32>,
+    // 함수 포인터 호출이면 function은 호출한 식
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pointer: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SummaryStatement {
+    // loop면 for/while/do/range for, allocation이면 할당 함수
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub line: u32,
+    pub endline: u32,
+    // 시작 라인 코드 (return, allocation만)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+/// 함수 본문을 전부 읽기 전에 볼 요약
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FunctionSummary {
+    pub qualified_name: String,
+    pub signature: String,
+    pub filename: String,
+    pub line: u32,
+    pub endline: u32,
+    pub parameters: Vec<SummaryVariable>,
+    pub locals: Vec<SummaryVariable>,
+    // 호출한 함수별로 라인을 모음 (처음 호출한 순서)
+    pub calls: Vec<SummaryCall>,
+    pub returns: Vec<SummaryStatement>,
+    pub loops: Vec<SummaryStatement>,
+    pub allocations: Vec<SummaryStatement>,
+}
+
+// 쿼리 행을 함수(파일, 정의 라인)별로 묶음. code는 비워 둠
+fn function_summaries_from_rows(mut rows: Vec<SummaryRowParse>) -> Vec<FunctionSummary> {
+    // 같은 라인의 파라미터는 위치(detail) 순서
+    rows.sort_by_key(|row| (row.filename.clone(), row.startline, row.line, row.detail.parse::<u32>().ok()));
+
+    let mut summaries: Vec<FunctionSummary> = rows
+        .iter()
+        .filter(|row| row.section == "signature")
+        .map(|row| FunctionSummary {
+            qualified_name: row.name.clone(),
+            signature: row.type_name.clone(),
+            filename: row.filename.clone(),
+            line: row.line,
+            endline: row.endline,
+            parameters: Vec::new(),
+            locals: Vec::new(),
+            calls: Vec::new(),
+            returns: Vec::new(),
+            loops: Vec::new(),
+            allocations: Vec::new(),
+        })
+        .collect();
+    summaries.dedup_by(|a, b| a.filename == b.filename && a.line == b.line);
+
+    for row in rows {
+        let Some(summary) = summaries.iter_mut().find(|s| s.filename == row.filename && s.line == row.startline) else {
+            continue;
+        };
+        let statement = |kind: Option<String>| SummaryStatement { kind, line: row.line, endline: row.endline, code: None };
+        match row.section.as_str() {
+            "parameter" => summary.parameters.push(SummaryVariable {
+                name: row.name,
+                type_name: row.type_name,
+                line: row.line,
+                is_static: false,
+            }),
+            "local" => summary.locals.push(SummaryVariable {
+                name: row.name,
+                type_name: row.type_name,
+                line: row.line,
+                is_static: row.detail == "static",
+            }),
+            "call" => {
+                let pointer = row.detail == "pointer";
+                match summary.calls.iter_mut().find(|call| call.function == row.name && call.pointer == pointer) {
+                    Some(call) => {
+                        if !call.lines.contains(&row.line) {
+                            call.lines.push(row.line);
+                        }
+                    }
+                    None => summary.calls.push(SummaryCall { function: row.name, lines: vec![row.line], pointer }),
+                }
+            }
+            "return" => summary.returns.push(statement(None)),
+            "loop" => summary.loops.push(statement(Some(row.name.clone()))),
+            "allocation" => summary.allocations.push(statement(Some(row.name.clone()))),
+            _ => {}
+        }
+    }
+
+    summaries
+}
+
+// find_var_refs에서 접근 위치 앞뒤로 보여줄 라인 수
+const VAR_REF_CONTEXT_LINES: u32 = 2;
+
+/// path-problem 경고 하나와 source에서 sink까지의 경로들
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PathAlert {
+    pub message: String,
+    pub sink: Option<PathStep>,
+    // 같은 경고에 경로가 여러 개일 수 있음
+    pub paths: Vec<Vec<PathStep>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathStep {
+    pub filename: String,
+    pub line: u32,
+    pub column: Option<u32>,
+    // 노드 설명 (변수명, 식)
+    pub node: Option<String>,
+    // 해당 라인 코드. 파일을 읽을 수 없으면 None
+    pub code: Option<String>,
+}
+
+// SARIF 결과를 PathAlert로 바꾸면서 각 단계의 코드를 소스에서 읽어 붙임
+fn path_alerts_from_sarif(sarif: &SarifLog, src_path: &str, fs: &FileSystem) -> Vec<PathAlert> {
+    let step = |location: &crate::tools::sarif::FindingLocation| {
+        let filepath = PathBuf::from(src_path).join(&location.filename);
+        let code = fs.read_file_lines(&filepath, location.startline, location.startline)
+            .ok()
+            .and_then(|lines| lines.first().map(|line| line.trim().to_string()));
+        PathStep {
+            filename: location.filename.clone(),
+            line: location.startline,
+            column: location.column,
+            node: location.message.clone(),
+            code,
+        }
+    };
+
+    sarif.findings()
+        .into_iter()
+        .map(|finding| PathAlert {
+            message: finding.message.clone(),
+            sink: finding.location.as_ref().map(step),
+            paths: finding.flows
+                .iter()
+                .map(|flow| flow.iter().map(step).collect())
+                .collect(),
+        })
+        .collect()
+}
+
 impl CodeQLAnalyzer{
     pub fn new(runner: CodeQLRunner) -> Self {
         CodeQLAnalyzer {
@@ -206,72 +1205,118 @@ impl CodeQLAnalyzer{
         }
     }
 
-    pub async fn find_var_definitions(&self, filename: &str, line: u32, varname: &str) -> Result<String> {
-        let query = format!(r#"
-        import cpp
+    pub fn runner(&self) -> &CodeQLRunner {
+        &self.runner
+    }
 
-        from VariableAccess a, Variable v
-        where
-        a.getFile().getRelativePath() = "{}" and
-        a.getLocation().getStartLine() = {} and
-        a.getTarget() = v and
-        v.getName() = "{}"
-        select 
-        v.getFile().getRelativePath() as filename,
-        v.getLocation().getStartLine() as startline,
-        v.getLocation().getEndLine() as endline
-        "#, filename, line, varname);
+    /// 해당 위치에서 사용된 변수의 정의
+    ///
+    /// 결과는 항상 배열. column을 주면 그 열의 접근만 보고, 그래도 후보가 여러 개면
+    /// (같은 라인의 같은 이름, 매크로 안의 접근 등) kind, scope, shadows로 구분함
+    pub async fn find_var_definitions(&self, filename: &str, line: u32, column: Option<u32>, varname: &str) -> Result<String> {
+        let query = queries::var_definitions(self.runner.language()?, filename, line, column, varname)?;
         let csv_result = self.runner.run_query(&query).await?;
-        let parsed: Vec<SourceInfoParse> = parse_csv(&csv_result)?;
-        
+        let mut parsed: Vec<VarDefinitionParse> = parse_csv(&csv_result)?;
+
         if parsed.is_empty() {
             return Err(AppError::CodeQLError("No results".to_string()));
-        }else if parsed.len() > 1 {
-            return Err(AppError::CodeQLError("Multiple results".to_string()));
         }
 
-        let source_info = &parsed[0];
-        let filepath = PathBuf::from(&self.runner.src_path).join(&source_info.filename);
-        let source_code = self.fs.read_file_lines(&filepath, source_info.startline, source_info.endline)?;
-        let result = SourceInfoResult {
-            filename: filepath.display().to_string(),
-            line: source_info.startline,
-            code: source_code.join("\n"),
-        };
-        Ok(serde_json::to_string_pretty(&result)?)
+        // 한 라인에서 같은 변수에 여러 번 접근하면 같은 정의가 반복됨
+        parsed.sort_by(|a, b| (&a.filename, a.startline, a.column).cmp(&(&b.filename, b.startline, b.column)));
+        parsed.dedup_by(|a, b| a.filename == b.filename && a.startline == b.startline);
+
+        let mut results = Vec::new();
+        for definition in parsed {
+            let filepath = PathBuf::from(&self.runner.src_path).join(&definition.filename);
+            let source_code = self.fs.read_file_lines(&filepath, definition.startline, definition.endline)?;
+            results.push(VarDefinitionResult {
+                filename: filepath.display().to_string(),
+                line: definition.startline,
+                code: source_code.join("\n"),
+                kind: definition.kind,
+                scope: definition.scope,
+                shadows: definition.shadows
+                    .split("; ")
+                    .filter(|shadow| !shadow.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                column: definition.column,
+            });
+        }
+
+        Ok(serde_json::to_string_pretty(&results)?)
     }
 
-    pub async fn find_function_implementation(&self, filename: &str, line: u32, funcname: &str) -> Result<String> {
-        let query = format!(r#"
-        import cpp
-
-        from FunctionCall fc, Function target, string is_virtual
-        where
-        fc.getFile().getRelativePath() = "{}" and
-        fc.getLocation().getStartLine() = {} and
-        target = fc.getTarget() and
-        (
-            target.getName() = "{}"
-            or target.getQualifiedName().matches("%{}%")
-        ) and
-        (
-            (target instanceof VirtualFunction and is_virtual = "true")
-            or
-            (not target instanceof VirtualFunction and is_virtual = "false")
-        )
-        select 
-        target.getQualifiedName() as qualified_name,
-        target.getFile().getRelativePath() as filename,
-        target.getLocation().getStartLine() as startline,
-        target.getLocation().getEndLine() as endline,
-        is_virtual
-        "#, filename, line, funcname, funcname);
+    /// 해당 위치에서 사용된 변수의 모든 접근 위치를 반환
+    ///
+    /// 각 접근은 읽기(read), 쓰기(write), 주소 획득(address), 함수 인자 전달(argument)로 구분되고
+    /// 앞뒤 코드 몇 줄이 함께 반환됨
+    pub async fn find_var_refs(&self, filename: &str, line: u32, column: Option<u32>, varname: &str) -> Result<String> {
+        let query = queries::var_refs(self.runner.language()?, filename, line, column, varname)?;
+
+        let csv_result = self.runner.run_query(&query).await?;
+        let mut parsed: Vec<VarRefParse> = parse_csv(&csv_result)?;
+
+        if parsed.is_empty() {
+            return Err(AppError::CodeQLError("No results".to_string()));
+        }
+
+        // 한 접근이 여러 종류일 수 있으므로 (예: 인자로 넘긴 x++) 위치별로 묶음
+        parsed.sort_by(|a, b| (&a.filename, a.line, a.column).cmp(&(&b.filename, b.line, b.column)));
+        let mut files: HashMap<String, Vec<String>> = HashMap::new();
+        let mut results: Vec<VarRefResult> = Vec::new();
+        for access in parsed {
+            let filepath = PathBuf::from(&self.runner.src_path).join(&access.filename);
+            let filepath = filepath.display().to_string();
+            if let Some(last) = results.last_mut() {
+                if last.filename == filepath && last.line == access.line && last.column == access.column {
+                    if !last.kinds.contains(&access.kind) {
+                        last.kinds.push(access.kind);
+                    }
+                    continue;
+                }
+            }
+
+            if !files.contains_key(&filepath) {
+                let content = self.fs.read_file(&filepath)?;
+                files.insert(filepath.clone(), content.lines().map(|l| l.to_string()).collect());
+            }
+            let lines = &files[&filepath];
+            let start = access.line.saturating_sub(VAR_REF_CONTEXT_LINES).max(1) as usize;
+            let end = ((access.line + VAR_REF_CONTEXT_LINES) as usize).min(lines.len());
+            let code = lines.get(start - 1..end).map(|l| l.join("\n")).unwrap_or_default();
+
+            results.push(VarRefResult {
+                filename: filepath,
+                line: access.line,
+                column: access.column,
+                kinds: vec![access.kind],
+                function: access.function,
+                code,
+            });
+        }
+
+        Ok(serde_json::to_string_pretty(&results)?)
+    }
+
+    pub async fn find_function_implementation(&self, filename: &str, line: u32, column: Option<u32>, funcname: &str) -> Result<String> {
+        let query = queries::function_implementation(self.runner.language()?, filename, line, column, funcname)?;
         
         let csv_result = self.runner.run_query(&query).await?;
         let parsed: Vec<FunctionInfoParse> = parse_csv(&csv_result)?;
         
         if parsed.is_empty() {
-            return Err(AppError::CodeQLError("No function call found at specified location".to_string()));
+            // C 코드의 "함수"는 매크로인 경우가 많음
+            let macros = match self.runner.language()? {
+                Language::Cpp => self.macro_results(filename, line, column, funcname).await?,
+                _ => Vec::new(),
+            };
+            if macros.is_empty() {
+                return Err(AppError::CodeQLError("No function call found at specified location".to_string()));
+            }
+            let results: Vec<_> = macros.into_iter().map(ImplementationResult::Macro).collect();
+            return Ok(serde_json::to_string_pretty(&results)?);
         }
 
         let mut results = Vec::new();
@@ -296,26 +1341,241 @@ impl CodeQLAnalyzer{
                 result.overrides = Some(self.find_function_overrides(&func_info.qualified_name).await?);
             }
 
-            results.push(result);
+            results.push(ImplementationResult::Function(result));
+        }
+
+        Ok(serde_json::to_string_pretty(&results)?)
+    }
+
+    /// 해당 위치의 매크로 호출을 찾아서 `#define` 정의와 인자를 치환한 결과를 반환
+    pub async fn find_macro(&self, filename: &str, line: u32, column: Option<u32>, name: &str) -> Result<String> {
+        let results = self.macro_results(filename, line, column, name).await?;
+        if results.is_empty() {
+            return Err(AppError::CodeQLError("No macro invocation found at specified location".to_string()));
+        }
+        Ok(serde_json::to_string_pretty(&results)?)
+    }
+
+    // 찾지 못하면 빈 배열. 쿼리 실패는 그대로 에러
+    async fn macro_results(&self, filename: &str, line: u32, column: Option<u32>, name: &str) -> Result<Vec<MacroResult>> {
+        let query = queries::macro_invocation(self.runner.language()?, filename, line, column, name)?;
+        let csv_result = self.runner.run_query(&query).await?;
+        let mut parsed: Vec<MacroParse> = parse_csv(&csv_result)?;
+
+        // 호출(column)마다 인자를 순서대로 모음
+        parsed.sort_by_key(|row| (row.column, row.index));
+        let mut results: Vec<MacroResult> = Vec::new();
+        for row in parsed {
+            if let Some(last) = results.last_mut() {
+                if last.column == row.column {
+                    last.arguments.push(row.argument);
+                    last.expanded_arguments.push(row.expanded);
+                    continue;
+                }
+            }
+
+            let code = if row.filename.is_empty() {
+                None
+            } else {
+                let filepath = PathBuf::from(&self.runner.src_path).join(&row.filename);
+                self.fs.read_file_lines(&filepath, row.startline, row.endline).ok().map(|lines| lines.join("\n"))
+            };
+            results.push(MacroResult {
+                name: macro_name(&row.head).to_string(),
+                head: row.head,
+                body: row.body,
+                filename: row.filename,
+                line: row.startline,
+                code,
+                column: row.column,
+                arguments: if row.index < 0 { Vec::new() } else { vec![row.argument] },
+                expanded_arguments: if row.index < 0 { Vec::new() } else { vec![row.expanded] },
+                expression: Some(row.expression).filter(|e| !e.is_empty()),
+                expression_type: Some(row.expression_type).filter(|t| !t.is_empty()),
+                expansion: String::new(),
+                approximate: true,
+            });
+        }
+
+        for result in &mut results {
+            result.expansion = expand_macro(&result.head, &result.body, &result.arguments, &result.expanded_arguments);
+        }
+        Ok(results)
+    }
+
+    /// 호출 위치 없이 이름으로 함수 정의를 찾음
+    ///
+    /// 오버로드나 번역 단위마다 따로 있는 static 함수는 모두 반환하고,
+    /// filename을 주면 그 파일에 정의된 것만 반환함
+    pub async fn find_function_code(&self, filename: Option<&str>, funcname: &str) -> Result<String> {
+        let query = queries::function_code(self.runner.language()?, filename, funcname)?;
+
+        let csv_result = self.runner.run_query(&query).await?;
+        let mut parsed: Vec<FunctionCodeParse> = parse_csv(&csv_result)?;
+        parsed.sort_by(|a, b| (&a.filename, a.startline).cmp(&(&b.filename, b.startline)));
+        parsed.dedup_by(|a, b| a.filename == b.filename && a.startline == b.startline);
+
+        if parsed.is_empty() {
+            return Err(AppError::CodeQLError(format!("No definition of {} found", funcname)));
+        }
+
+        let mut results = Vec::new();
+        for func_info in parsed {
+            let filepath = PathBuf::from(&self.runner.src_path).join(&func_info.filename);
+            let source_code = self.fs.read_file_lines(&filepath, func_info.startline, func_info.endline)?;
+            results.push(FunctionCodeResult {
+                qualified_name: func_info.qualified_name,
+                signature: func_info.signature,
+                filename: filepath.display().to_string(),
+                line: func_info.startline,
+                code: source_code.join("\n"),
+                is_static: func_info.is_static == "true",
+            });
+        }
+
+        Ok(serde_json::to_string_pretty(&results)?)
+    }
+
+    /// 함수 본문 대신 시그니처, 파라미터, 지역 변수, 호출한 함수, return, 반복문, 메모리 할당 위치를 요약
+    ///
+    /// 긴 함수에서 어느 부분을 읽을지 고르는 용도. filename은 find_function_code와 같음
+    pub async fn summarize_function(&self, filename: Option<&str>, funcname: &str) -> Result<String> {
+        let query = queries::function_summary(self.runner.language()?, filename, funcname)?;
+        let csv_result = self.runner.run_query(&query).await?;
+        let parsed: Vec<SummaryRowParse> = parse_csv(&csv_result)?;
+
+        let mut summaries = function_summaries_from_rows(parsed);
+        if summaries.is_empty() {
+            return Err(AppError::CodeQLError(format!("No definition of {} found", funcname)));
+        }
+
+        for summary in &mut summaries {
+            let filepath = PathBuf::from(&self.runner.src_path).join(&summary.filename);
+            let lines = self.fs.read_file_lines(&filepath, summary.line, summary.endline)?;
+            let code = |line: u32| {
+                lines.get(line.checked_sub(summary.line)? as usize).map(|code| code.trim().to_string())
+            };
+            for statement in summary.returns.iter_mut().chain(summary.allocations.iter_mut()) {
+                statement.code = code(statement.line);
+            }
+            summary.filename = filepath.display().to_string();
+        }
+        Ok(serde_json::to_string_pretty(&summaries)?)
+    }
+
+    /// path-problem 쿼리를 실행해서 경고마다 단계별 위치와 코드를 반환
+    pub async fn run_path_problem(&self, query: &str) -> Result<Vec<PathAlert>> {
+        let sarif = self.runner.run_path_query(query).await?;
+        Ok(path_alerts_from_sarif(&sarif, &self.runner.src_path, &self.fs))
+    }
+
+    /// 쿼리 스위트를 실행해서 결과를 번호와 함께 반환 (limit개까지)
+    pub async fn analyze_suite(&self, suite: &str, limit: usize) -> Result<String> {
+        let findings: Vec<Finding> = self.runner.analyze(suite).await?.findings();
+        let total = findings.len();
+        let results: Vec<_> = findings
+            .into_iter()
+            .take(limit)
+            .enumerate()
+            .map(|(index, finding)| serde_json::json!({"index": index, "finding": finding}))
+            .collect();
+
+        Ok(serde_json::to_string_pretty(&serde_json::json!({
+            "total": total,
+            "results": results,
+        }))?)
+    }
+
+    /// source에서 sink로 가는 taint 흐름의 경로. 빈 배열이면 흐름 없음
+    pub async fn find_dataflow(&self, source: &FlowSpec, sink: &FlowSpec) -> Result<String> {
+        let query = queries::dataflow(self.runner.language()?, source, sink)?;
+        let alerts = self.run_path_problem(&query).await?;
+        Ok(serde_json::to_string_pretty(&alerts)?)
+    }
+
+    /// 이름이 패턴과 맞는 함수, 전역 변수, 타입, 매크로를 유사도 순으로 limit개까지 반환
+    pub async fn search_symbols(&self, pattern: &SymbolPattern, kinds: &[SymbolKind], limit: usize) -> Result<String> {
+        let query = queries::symbols(self.runner.language()?, &pattern.to_regex(), kinds)?;
+        let csv_result = self.runner.run_query(&query).await?;
+        let parsed: Vec<Symbol> = parse_csv(&csv_result)?;
+        let total = parsed.len();
+
+        Ok(serde_json::to_string_pretty(&serde_json::json!({
+            "total": total,
+            "results": symbols::rank(parsed, pattern, limit),
+        }))?)
+    }
+
+    /// 해당 위치에서 쓰인 타입의 정의. typedef를 따라가서 struct/class/union/enum 정의,
+    /// 멤버(타입, 바이트 오프셋, 크기), 베이스 클래스를 반환
+    pub async fn find_type_definition(&self, filename: &str, line: u32, column: Option<u32>, typename: &str) -> Result<String> {
+        let query = queries::type_definition(self.runner.language()?, filename, line, column, typename)?;
+        let csv_result = self.runner.run_query(&query).await?;
+        let parsed: Vec<TypeRowParse> = parse_csv(&csv_result)?;
+
+        let mut result = type_definition_from_rows(parsed);
+        if result.typedefs.is_empty() && result.definitions.is_empty() {
+            return Err(AppError::CodeQLError("No results".to_string()));
+        }
+        result.rank_definitions(filename, MAX_TYPE_DEFINITIONS);
+
+        for definition in &mut result.definitions {
+            if definition.filename.is_empty() {
+                continue;
+            }
+            let filepath = PathBuf::from(&self.runner.src_path).join(&definition.filename);
+            definition.code = self.fs.read_file_lines(&filepath, definition.line, definition.endline).ok().map(|lines| lines.join("\n"));
+            definition.filename = filepath.display().to_string();
+        }
+        Ok(serde_json::to_string_pretty(&result)?)
+    }
+
+    /// 함수에서 시작해 depth 단계까지 호출하는(callees) 또는 호출되는(callers) 함수 트리
+    ///
+    /// 같은 이름의 함수가 여러 개면 루트도 여러 개. dot이면 Graphviz DOT도 함께 반환
+    pub async fn call_graph(&self, function: &str, direction: CallDirection, depth: u32, dot: bool) -> Result<String> {
+        let query = queries::call_graph(self.runner.language()?, function, direction, depth)?;
+        let csv_result = self.runner.run_query(&query).await?;
+        let edges: Vec<CallEdge> = parse_csv(&csv_result)?;
+        let graph = CallGraph::new(&edges, direction, depth);
+
+        let mut result = serde_json::json!({
+            "function": function,
+            "direction": direction,
+            "depth": depth,
+            "roots": graph.trees(),
+        });
+        if dot {
+            result["dot"] = serde_json::Value::String(graph.to_dot());
+        }
+        Ok(serde_json::to_string_pretty(&result)?)
+    }
+
+    /// 함수를 호출하는 모든 곳을 호출한 함수 단위로 묶어서 반환
+    ///
+    /// filename을 주면 그 파일에 정의된 함수만 대상으로 함 (같은 이름의 static 함수 구분용)
+    pub async fn find_function_refs(&self, filename: Option<&str>, funcname: &str) -> Result<String> {
+        let query = queries::function_refs(self.runner.language()?, filename, funcname)?;
+
+        let csv_result = self.runner.run_query(&query).await?;
+        let parsed: Vec<FunctionRefParse> = parse_csv(&csv_result)?;
+
+        if parsed.is_empty() {
+            return Err(AppError::CodeQLError(format!("No calls to {} found", funcname)));
+        }
+
+        let mut results = function_refs_from_rows(parsed);
+        for result in &mut results {
+            let filepath = PathBuf::from(&self.runner.src_path).join(&result.filename);
+            result.code = self.fs.read_file_lines(&filepath, result.line, result.endline)?.join("\n");
+            result.filename = filepath.display().to_string();
         }
 
         Ok(serde_json::to_string_pretty(&results)?)
     }
 
     async fn find_function_overrides(&self, qualified_name: &str) -> Result<Vec<FunctionInfoResult>> {
-        let query = format!(r#"
-        import cpp
-
-        from VirtualFunction base, Function override
-        where
-        base.getQualifiedName() = "{}" and
-        override = base.getAnOverridingFunction()
-        select 
-        override.getQualifiedName() as qualified_name,
-        override.getFile().getRelativePath() as filename,
-        override.getLocation().getStartLine() as startline,
-        override.getLocation().getEndLine() as endline
-        "#, qualified_name);
+        let query = queries::function_overrides(self.runner.language()?, qualified_name)?;
 
         let csv_result = self.runner.run_query(&query).await?;
         let parsed: Vec<OverrideInfoParse> = parse_csv(&csv_result)?;
@@ -354,7 +1614,242 @@ async fn test_find_function_implementation(){
     let runner = CodeQLRunner::new("/home/goat/aaa/curl", "/home/goat/aaa/curl/curl.ql")
         .expect("CodeQL CLI가 설치되어 있어야 합니다");
     let analyzer = CodeQLAnalyzer::new(runner);
-    let result = analyzer.find_function_implementation("src/var.c", 465, "file2memory_range").await;
+    let result = analyzer.find_function_implementation("src/var.c", 465, None, "file2memory_range").await;
+    println!("Result: {:#?}", result);
+    assert!(result.is_ok());
+}
+#[tokio::test]
+#[ignore = "CodeQL CLI와 로컬 curl 데이터베이스가 필요합니다"]
+async fn test_find_function_code(){
+    let runner = CodeQLRunner::new("/home/goat/aaa/curl", "/home/goat/aaa/curl/curl.ql")
+        .expect("CodeQL CLI가 설치되어 있어야 합니다");
+    let analyzer = CodeQLAnalyzer::new(runner);
+    let result = analyzer.find_function_code(None, "file2memory_range").await;
     println!("Result: {:#?}", result);
     assert!(result.is_ok());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_database_options_args() {
+    assert!(DatabaseOptions::default().args().is_empty());
+    let options = DatabaseOptions {
+        command: Some("make -j8".to_string()),
+        overwrite: true,
+        threads: Some(0),
+        build_mode: Some(BuildMode::Manual),
+        timeout: None,
+    };
+    assert_eq!(
+        options.args(),
+        vec!["--command=make -j8", "--overwrite", "--threads=0", "--build-mode=manual"]
+    );
+}
+
+#[test]
+fn test_stderr_tail() {
+    assert_eq!(stderr_tail("a\nb\nc\n", 2), "b\nc");
+    assert_eq!(stderr_tail("only", 5), "only");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_output_with_timeout() {
+    let mut command = tokio::process::Command::new("sleep");
+    command.arg("5");
+    let started = std::time::Instant::now();
+    let result = output_with_timeout(command, "sleep", Some(Duration::from_millis(100))).await;
+    assert!(matches!(result, Err(AppError::Timeout { .. })));
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    let mut command = tokio::process::Command::new("echo");
+    command.arg("ok");
+    let output = output_with_timeout(command, "echo", Some(Duration::from_secs(5))).await.unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+}
+
+#[test]
+fn test_path_alerts_from_sarif() {
+    let src = crate::tools::workspace::test_dir("path_alerts");
+    fs::create_dir_all(src.join("src")).unwrap();
+    fs::write(src.join("src/main.c"), "int main(int argc, char **argv) {\n    char buf[8];\n    strcpy(buf, argv[1]);\n}\n").unwrap();
+
+    let sarif = SarifLog::parse(r#"{"runs": [{"tool": {"driver": {"name": "CodeQL"}}, "results": [{
+        "ruleId": "codeql-agent/path-query",
+        "message": {"text": "argv flows to strcpy"},
+        "locations": [{"physicalLocation": {"artifactLocation": {"uri": "src/main.c"}, "region": {"startLine": 3, "startColumn": 17}}}],
+        "codeFlows": [{"threadFlows": [{"locations": [
+            {"location": {"physicalLocation": {"artifactLocation": {"uri": "src/main.c"}, "region": {"startLine": 1, "startColumn": 27}}, "message": {"text": "argv"}}},
+            {"location": {"physicalLocation": {"artifactLocation": {"uri": "src/main.c"}, "region": {"startLine": 3, "startColumn": 17}}, "message": {"text": "access to array"}}},
+            {"location": {"physicalLocation": {"artifactLocation": {"uri": "src/missing.c"}, "region": {"startLine": 9}}}}
+        ]}]}]
+    }]}]}"#).unwrap();
+    let alerts = path_alerts_from_sarif(&sarif, &src.path().display().to_string(), &FileSystem::new());
+
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].sink.as_ref().unwrap().code.as_deref(), Some("strcpy(buf, argv[1]);"));
+    let steps = &alerts[0].paths[0];
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0].node.as_deref(), Some("argv"));
+    assert_eq!(steps[0].code.as_deref(), Some("int main(int argc, char **argv) {"));
+    assert_eq!(steps[1].line, 3);
+    assert_eq!(steps[2].code, None);
+}
+
+#[test]
+fn test_type_definition_from_rows() {
+    let rows: Vec<TypeRowParse> = parse_csv(
+        "section,owner,name,type,filename,startline,endline,offset,size\n\
+         member,Curl_URL,host,char *,lib/urlapi.c,70,70,8,8\n\
+         typedef,1,CURLU_alias,CURLU,lib/a.h,3,3,-1,-1\n\
+         definition,,Curl_URL,struct,lib/urlapi.c,66,80,-1,48\n\
+         member,Curl_URL,scheme,char *,lib/urlapi.c,67,67,0,8\n\
+         member,Curl_URL,flags,unsigned int : 3,lib/urlapi.c,79,79,-1,4\n\
+         typedef,0,CURLU,Curl_URL,include/curl/urlapi.h,30,30,-1,48\n\
+         typedef,0,CURLU,Curl_URL,include/curl/urlapi.h,30,30,-1,48\n\
+         base,Curl_URL,Base,virtual,lib/b.h,5,9,-1,16\n",
+    ).unwrap();
+    let result = type_definition_from_rows(rows);
+
+    let typedefs: Vec<_> = result.typedefs.iter().map(|t| (t.name.as_str(), t.aliased.as_str())).collect();
+    assert_eq!(typedefs, vec![("CURLU", "Curl_URL"), ("CURLU_alias", "CURLU")]);
+
+    assert_eq!(result.definitions.len(), 1);
+    let definition = &result.definitions[0];
+    assert_eq!((definition.kind.as_str(), definition.size, definition.endline), ("struct", Some(48), 80));
+    let members: Vec<_> = definition.members.iter().map(|m| (m.name.as_str(), m.offset)).collect();
+    assert_eq!(members, vec![("scheme", Some(0)), ("host", Some(8)), ("flags", None)]);
+    assert!(definition.bases[0].is_virtual);
+}
+
+#[test]
+fn test_rank_type_definitions() {
+    let mut rows = String::from("section,owner,name,type,filename,startline,endline,offset,size\n");
+    for i in 0..8 {
+        rows.push_str(&format!("definition,,ctx,struct,lib/f{}.c,{},{},-1,-1\n", i, i + 1, i + 5));
+    }
+    rows.push_str("member,ctx,fd,int,lib/f3.c,5,5,0,4\n");
+    rows.push_str("definition,,ctx,struct,lib/url.c,40,50,-1,16\n");
+    let mut result = type_definition_from_rows(parse_csv(&rows).unwrap());
+    result.rank_definitions("lib/url.c", 3);
+
+    let files: Vec<_> = result.definitions.iter().map(|d| d.filename.as_str()).collect();
+    // 요청한 파일, 멤버가 있는 정의(f3), 나머지 순
+    assert_eq!(files, vec!["lib/url.c", "lib/f3.c", "lib/f0.c"]);
+    assert_eq!(result.omitted, 6);
+}
+
+#[test]
+fn test_expand_macro() {
+    let args = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    // 인자 안에 매크로가 없으면 전개된 인자도 같음
+    let expand = |head: &str, body: &str, values: &[&str]| expand_macro(head, body, &args(values), &args(values));
+
+    assert_eq!(expand("CURLMIN(x,y)", "((x)<(y)?(x):(y))", &["len", " sizeof(buf) "]),
+        "((len)<(sizeof(buf))?(len):(sizeof(buf)))");
+    // 객체형 매크로는 body 그대로
+    assert_eq!(expand("MAX_LEN", " 256UL ", &[]), "256UL");
+    // 문자열 안의 이름, 숫자 접미사는 치환하지 않음
+    assert_eq!(expand("LOG(x)", "printf(\"x=%d\\n\", x)", &["n"]), "printf(\"x=%d\\n\", n)");
+    assert_eq!(expand("SHIFT(U)", "1U << U", &["3"]), "1U << 3");
+    // #, ##, 가변 인자
+    assert_eq!(expand("STR(a)", "#a", &["foo \"bar\""]), "\"foo \\\"bar\\\"\"");
+    assert_eq!(expand("CAT(a, b)", "a ## _ ## b", &["curl", "init"]), "curl_init");
+    assert_eq!(expand("infof(data, ...)", "Curl_infof(data, __VA_ARGS__)", &["d", "\"%s\"", "msg"]),
+        "Curl_infof(d, \"%s\", msg)");
+
+    // 일반 치환은 CodeQL이 전개한 인자, #과 ##의 피연산자는 원래 인자
+    let raw = args(&["MAX_LEN"]);
+    let expanded = args(&["256UL"]);
+    assert_eq!(expand_macro("SIZE(n)", "(n) * 2", &raw, &expanded), "(256UL) * 2");
+    assert_eq!(expand_macro("NAME(n)", "#n", &raw, &expanded), "\"MAX_LEN\"");
+    assert_eq!(expand_macro("PREFIX(n)", "CURL_ ## n", &raw, &expanded), "CURL_MAX_LEN");
+    assert_eq!(expand_macro("SUFFIX(n)", "n ## _T", &raw, &expanded), "MAX_LEN_T");
+
+    assert_eq!(macro_name("CURLMIN(x,y)"), "CURLMIN");
+    assert_eq!(macro_params("F()"), Some(Vec::new()));
+}
+
+#[test]
+fn test_function_refs_from_rows() {
+    let rows: Vec<FunctionRefParse> = parse_csv(
+        "caller,filename,line,startline,endline\n\
+         Curl_close,lib/url.c,420,400,450\n\
+         parse_url,lib/urlapi.c,90,80,120\n\
+         Curl_close,lib/url.c,410,400,450\n\
+         Curl_close,lib/url.c,410,400,450\n\
+         Curl_close,lib/multi.c,30,20,40\n",
+    ).unwrap();
+    let results = function_refs_from_rows(rows);
+    let callers: Vec<_> = results.iter().map(|r| (r.filename.as_str(), r.caller.as_str())).collect();
+    assert_eq!(callers, vec![("lib/multi.c", "Curl_close"), ("lib/url.c", "Curl_close"), ("lib/urlapi.c", "parse_url")]);
+    // 같은 함수 안의 호출은 라인만 모이고 중복은 한 번
+    assert_eq!(results[1].call_lines, vec![410, 420]);
+    assert_eq!((results[1].line, results[1].endline), (400, 450));
+}
+
+#[test]
+fn test_implementation_result_kind() {
+    let function = ImplementationResult::Function(FunctionInfoResult {
+        qualified_name: "Curl_close".to_string(),
+        filename: "lib/url.c".to_string(),
+        line: 10,
+        code: "CURLcode Curl_close(...)".to_string(),
+        is_virtual: false,
+        overrides: None,
+    });
+    let macro_hit = ImplementationResult::Macro(MacroResult {
+        name: "free".to_string(),
+        head: "free(ptr)".to_string(),
+        body: "Curl_cfree(ptr)".to_string(),
+        filename: "lib/memdebug.h".to_string(),
+        line: 3,
+        code: None,
+        column: 5,
+        arguments: vec!["p".to_string()],
+        expanded_arguments: vec!["p".to_string()],
+        expression: Some("call to Curl_cfree".to_string()),
+        expression_type: Some("void".to_string()),
+        expansion: "Curl_cfree(p)".to_string(),
+        approximate: true,
+    });
+    let json = serde_json::to_value([function, macro_hit]).unwrap();
+    assert_eq!(json[0]["kind"], "function");
+    assert_eq!(json[0]["qualified_name"], "Curl_close");
+    assert_eq!(json[1]["kind"], "macro");
+    assert_eq!(json[1]["expansion"], "Curl_cfree(p)");
+}
+
+#[test]
+fn test_function_summaries_from_rows() {
+    let rows: Vec<SummaryRowParse> = parse_csv(
+        "filename,startline,section,name,type,line,endline,detail\n\
+         src/a.c,10,call,malloc,,14,14,\n\
+         src/a.c,10,parameter,len,size_t,10,10,1\n\
+         src/a.c,10,signature,copy,\"char * copy(const char *, size_t)\",10,30,\n\
+         src/a.c,10,parameter,src,const char *,10,10,0\n\
+         src/a.c,10,local,buf,char *,12,12,\n\
+         src/a.c,10,allocation,malloc,void *,14,14,\n\
+         src/a.c,10,loop,for,,16,20,\n\
+         src/a.c,10,call,memcpy,,18,18,\n\
+         src/a.c,10,call,malloc,,22,22,\n\
+         src/a.c,10,call,cb->fn,,24,24,pointer\n\
+         src/a.c,10,return,,,29,29,\n\
+         src/b.c,5,signature,copy,int copy(void),5,7,\n",
+    ).unwrap();
+    let summaries = function_summaries_from_rows(rows);
+    assert_eq!(summaries.len(), 2);
+
+    let summary = &summaries[0];
+    assert_eq!((summary.line, summary.endline), (10, 30));
+    let parameters: Vec<_> = summary.parameters.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(parameters, vec!["src", "len"]);
+    assert_eq!(summary.locals[0].type_name, "char *");
+    let calls: Vec<_> = summary.calls.iter().map(|c| (c.function.as_str(), c.lines.clone(), c.pointer)).collect();
+    assert_eq!(calls, vec![("malloc", vec![14, 22], false), ("memcpy", vec![18], false), ("cb->fn", vec![24], true)]);
+    assert_eq!(summary.loops[0].kind.as_deref(), Some("for"));
+    assert_eq!(summary.loops[0].endline, 20);
+    assert_eq!(summary.allocations[0].line, 14);
+    assert_eq!(summary.returns.len(), 1);
+
+    assert!(summaries[1].calls.is_empty());
+}
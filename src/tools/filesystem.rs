@@ -3,10 +3,16 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
-use crate::error::Result; 
+use crate::error::{AppError, Result};
 
 pub struct FileSystem;
 
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FileSystem {
     pub fn new() -> Self {
         FileSystem
@@ -22,8 +28,15 @@ impl FileSystem {
         let path = path.as_ref();
         let content = fs::read_to_string(path)?;
         let lines = content.lines().collect::<Vec<_>>();
+        // 모델이 넘긴 범위가 틀려도 패닉하지 않도록 검사 (끝 라인은 파일 끝으로 자름)
+        if startline == 0 || startline > endline || startline as usize > lines.len() {
+            return Err(AppError::InvalidArgument(format!(
+                "Invalid line range {}-{} for {} ({} lines)",
+                startline, endline, path.display(), lines.len()
+            )));
+        }
         let start = (startline - 1) as usize;
-        let end = (endline - 1) as usize;
+        let end = (endline as usize).min(lines.len()) - 1;
         Ok(lines[start..=end].iter().map(|s| s.to_string()).collect())
     }
 
@@ -97,4 +110,15 @@ mod tests {
         let parsed: Vec<FileEntry> = serde_json::from_str(&json).unwrap();
         assert!(!parsed.is_empty());
     }
+
+    #[test]
+    fn test_read_file_lines_invalid_range() {
+        let fs = FileSystem::new();
+        assert!(fs.read_file_lines("./Cargo.toml", 0, 1).is_err());
+        assert!(fs.read_file_lines("./Cargo.toml", 3, 2).is_err());
+        assert!(fs.read_file_lines("./Cargo.toml", 10000, 10001).is_err());
+        // 끝 라인이 파일보다 길면 파일 끝까지만 읽음
+        let lines = fs.read_file_lines("./Cargo.toml", 1, 10000).unwrap();
+        assert_eq!(lines[0], "[package]");
+    }
 }
@@ -0,0 +1,137 @@
+// language.rs - CodeQL 데이터베이스 언어
+
+use std::fmt;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Cpp,
+    Java,
+    Python,
+    JavaScript,
+    CSharp,
+    Go,
+    Ruby,
+    Swift,
+}
+
+impl Language {
+    /// CodeQL이 쓰는 언어 이름 (qlpack 이름, `import` 문에 사용)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Cpp => "cpp",
+            Language::Java => "java",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::CSharp => "csharp",
+            Language::Go => "go",
+            Language::Ruby => "ruby",
+            Language::Swift => "swift",
+        }
+    }
+
+    /// `codeql-database.yml`의 primaryLanguage 값을 해석
+    pub fn from_codeql_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "cpp" | "c" | "c-cpp" => Some(Language::Cpp),
+            "java" | "kotlin" | "java-kotlin" => Some(Language::Java),
+            "python" => Some(Language::Python),
+            "javascript" | "typescript" | "javascript-typescript" => Some(Language::JavaScript),
+            "csharp" => Some(Language::CSharp),
+            "go" => Some(Language::Go),
+            "ruby" => Some(Language::Ruby),
+            "swift" => Some(Language::Swift),
+            _ => None,
+        }
+    }
+
+    /// 데이터베이스 디렉터리의 `codeql-database.yml`에서 primaryLanguage를 읽음
+    ///
+    /// 파일이 없으면 IoError(NotFound), 언어를 모르면 UnsupportedLanguage
+    pub fn from_database<P: AsRef<Path>>(db_path: P) -> Result<Self> {
+        let metadata_path = db_path.as_ref().join("codeql-database.yml");
+        let metadata = std::fs::read_to_string(&metadata_path)?;
+        let name = metadata
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("primaryLanguage:"))
+            .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\''))
+            .ok_or_else(|| AppError::CodeQLError(format!(
+                "No primaryLanguage in {}",
+                metadata_path.display()
+            )))?;
+        Self::from_codeql_name(name)
+            .ok_or_else(|| AppError::UnsupportedLanguage(name.to_string()))
+    }
+
+    /// `import` 모듈 이름이 가리키는 언어 (예: `cpp`, `semmle.code.java.Maps`, `codeql.ruby.AST`)
+    pub fn from_import(module: &str) -> Option<Self> {
+        let segments: Vec<&str> = module.split('.').collect();
+        let name = match segments.as_slice() {
+            ["semmle", "code", name, ..] => *name,
+            ["semmle", name, ..] | ["codeql", name, ..] => *name,
+            [name, ..] => *name,
+            [] => return None,
+        };
+        match name {
+            "cpp" => Some(Language::Cpp),
+            "java" => Some(Language::Java),
+            "python" => Some(Language::Python),
+            "javascript" => Some(Language::JavaScript),
+            "csharp" => Some(Language::CSharp),
+            "go" => Some(Language::Go),
+            "ruby" => Some(Language::Ruby),
+            "swift" => Some(Language::Swift),
+            _ => None,
+        }
+    }
+
+    /// 쿼리의 import 문에서 언어를 찾음. 언어 라이브러리를 import하지 않으면 None
+    pub fn from_query(query: &str) -> Option<Self> {
+        query
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("import "))
+            .filter_map(|rest| rest.split_whitespace().next())
+            .find_map(Self::from_import)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::workspace::test_dir;
+
+    #[test]
+    fn test_from_database() {
+        let db = test_dir("lang_db");
+        std::fs::write(db.join("codeql-database.yml"), "---\nsourceLocationPrefix: /src\nbaselineLinesOfCode: 10\nprimaryLanguage: \"java\"\n").unwrap();
+        assert_eq!(Language::from_database(db.path()).unwrap(), Language::Java);
+        std::fs::write(db.join("codeql-database.yml"), "primaryLanguage: cobol\n").unwrap();
+        assert!(matches!(Language::from_database(db.path()), Err(AppError::UnsupportedLanguage(_))));
+        assert!(matches!(Language::from_database(db.join("missing")), Err(AppError::IoError(_))));
+    }
+
+    #[test]
+    fn test_from_query() {
+        assert_eq!(Language::from_query("\n        import cpp\n\n        from Function f select f"), Some(Language::Cpp));
+        assert_eq!(Language::from_query("import semmle.code.java.dataflow.DataFlow\nimport java"), Some(Language::Java));
+        assert_eq!(Language::from_query("import codeql.ruby.AST"), Some(Language::Ruby));
+        assert_eq!(Language::from_query("import DataFlow::PathGraph\nimport go"), Some(Language::Go));
+        assert_eq!(Language::from_query("select 1"), None);
+    }
+
+    #[test]
+    fn test_from_codeql_name() {
+        assert_eq!(Language::from_codeql_name("cpp"), Some(Language::Cpp));
+        assert_eq!(Language::from_codeql_name("javascript-typescript"), Some(Language::JavaScript));
+        assert_eq!(Language::from_codeql_name("cobol"), None);
+    }
+}
@@ -2,10 +2,144 @@
 //
 // 이 모듈은 에이전트가 사용할 수 있는 도구들을 제공합니다.
 
-// TODO: 하위 모듈 선언
+pub mod bqrs;        // bqrs JSON 결과 모델
+pub mod builtin;     // CodeQLAnalyzer, FileSystem을 감싼 기본 도구
+pub mod callgraph;   // 호출 그래프 트리, DOT 출력
+pub mod cache;       // 쿼리 결과 디스크 캐시
 pub mod codeql;      // CodeQL CLI 래퍼
 pub mod filesystem;  // 파일 시스템 작업
+pub mod language;    // 데이터베이스 언어
+pub mod pack;        // 언어별 쿼리 팩
+pub mod ql;          // QL 템플릿 빌더 (값 이스케이프)
+pub mod queries;     // 언어별 쿼리 템플릿
+pub mod query_server; // query-server2 JSON-RPC 클라이언트
+pub mod sarif;       // SARIF 결과 파서
+pub mod symbols;     // 심볼 검색 패턴과 유사도 순위
+pub mod workspace;   // 쿼리 작업 디렉터리
+
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::error::{AppError, Result};
 
 // Rust 학습 포인트:
 // - 모듈을 통해 코드를 논리적 단위로 분리
 // - pub으로 외부에 공개할 것과 비공개로 둘 것을 구분
+
+/// 모델에게 알려줄 도구 설명 (parameters는 JSON Schema)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+/// 모델이 호출할 수 있는 도구
+///
+/// 이름, 인자의 JSON Schema, 실행 함수를 선언하면
+/// 에이전트는 ToolRegistry를 통해 별도 코드 없이 광고하고 호출합니다.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// 인자의 JSON Schema
+    fn parameters(&self) -> Value;
+
+    async fn invoke(&self, args: Value) -> Result<Value>;
+
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            parameters: self.parameters(),
+        }
+    }
+}
+
+/// 등록된 도구들을 이름으로 찾아 실행
+#[derive(Default)]
+pub struct ToolRegistry {
+    // 모델에게 보여줄 순서를 유지하려고 등록 순서대로 보관
+    tools: Vec<Box<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        ToolRegistry { tools: Vec::new() }
+    }
+
+    /// 도구 등록. 같은 이름이 이미 있으면 교체
+    pub fn register<T: Tool + 'static>(&mut self, tool: T) -> &mut Self {
+        match self.tools.iter().position(|t| t.name() == tool.name()) {
+            Some(index) => self.tools[index] = Box::new(tool),
+            None => self.tools.push(Box::new(tool)),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Tool> {
+        self.tools.iter().find(|t| t.name() == name).map(|t| t.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.tools.iter().map(|t| t.name()).collect()
+    }
+
+    pub fn specs(&self) -> Vec<ToolSpec> {
+        self.tools.iter().map(|t| t.spec()).collect()
+    }
+
+    pub async fn invoke(&self, name: &str, args: Value) -> Result<Value> {
+        let tool = self
+            .get(name)
+            .ok_or_else(|| AppError::InvalidArgument(format!("Unknown tool: {}", name)))?;
+        tool.invoke(args).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct EchoTool(&'static str);
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            self.0
+        }
+
+        fn parameters(&self) -> Value {
+            json!({"type": "object", "properties": {"text": {"type": "string"}}})
+        }
+
+        async fn invoke(&self, args: Value) -> Result<Value> {
+            Ok(args["text"].clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_invoke() {
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool("첫 번째"));
+        let result = registry.invoke("echo", json!({"text": "hi"})).await.unwrap();
+        assert_eq!(result, json!("hi"));
+        assert!(registry.invoke("missing", json!({})).await.is_err());
+    }
+
+    #[test]
+    fn test_registry_replaces_same_name() {
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool("첫 번째")).register(EchoTool("두 번째"));
+        let specs = registry.specs();
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].description, "두 번째");
+    }
+}
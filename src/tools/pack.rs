@@ -0,0 +1,222 @@
+// pack.rs - 언어별 쿼리 팩
+//
+// 쿼리마다 새 qlpack을 만들면 매번 의존성을 다시 해석해야 합니다.
+// 언어마다 팩 하나를 만들어 `codeql pack install`을 한 번만 하고,
+// 생성된 codeql-pack.lock.yml을 이후 모든 쿼리가 같이 씁니다.
+// 팩 루트는 여러 프로세스가 같이 쓰므로 준비 단계는 파일 잠금 안에서 합니다.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::error::{AppError, Result};
+use crate::tools::codeql::output_with_timeout;
+use crate::tools::language::Language;
+
+const LOCK_FILE: &str = "codeql-pack.lock.yml";
+
+// pack install은 처음 한 번 라이브러리를 내려받으므로 쿼리보다 길게 잡음
+const INSTALL_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub struct QueryPacks {
+    root: PathBuf,
+    offline: bool,
+    additional_packs: Vec<PathBuf>,
+    // 이번 세션에서 준비를 마친 팩. 설치가 한 번만 돌도록 잠금 안에서 확인
+    ready: tokio::sync::Mutex<HashMap<Language, PathBuf>>,
+}
+
+impl Default for QueryPacks {
+    fn default() -> Self {
+        QueryPacks::new(std::env::temp_dir().join("codeql_agent").join("packs"))
+    }
+}
+
+impl QueryPacks {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        QueryPacks {
+            root: root.into(),
+            offline: false,
+            additional_packs: Vec::new(),
+            ready: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// `codeql pack install`을 하지 않고 로컬 팩 캐시(additional_packs, ~/.codeql/packages)만 사용
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// 라이브러리 팩을 찾을 추가 디렉터리 (`--additional-packs`)
+    pub fn with_additional_packs(mut self, packs: Vec<PathBuf>) -> Self {
+        self.additional_packs = packs;
+        self
+    }
+
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn additional_packs(&self) -> &[PathBuf] {
+        &self.additional_packs
+    }
+
+    pub fn pack_dir(&self, language: Language) -> PathBuf {
+        self.root.join(format!("{}-queries", language))
+    }
+
+    /// 언어 팩 디렉터리를 만들고 의존성을 설치. 이미 lockfile이 있으면 설치하지 않음
+    pub async fn ensure(&self, language: Language) -> Result<PathBuf> {
+        let mut ready = self.ready.lock().await;
+        if let Some(dir) = ready.get(&language) {
+            return Ok(dir.clone());
+        }
+
+        let dir = self.pack_dir(language);
+        fs::create_dir_all(&self.root)?;
+        let _lock = lock_file(self.root.join(format!("{}-queries.lock", language))).await?;
+        fs::create_dir_all(dir.join("queries"))?;
+        let qlpack_path = dir.join("qlpack.yml");
+        let qlpack_content = qlpack_yml(language);
+        // 내용이 바뀐 경우에만 다시 써서 lockfile이 무효화되지 않게 함
+        if fs::read_to_string(&qlpack_path).ok().as_deref() != Some(qlpack_content.as_str()) {
+            fs::write(&qlpack_path, &qlpack_content)?;
+            let _ = fs::remove_file(dir.join(LOCK_FILE));
+        }
+
+        if !self.offline && !dir.join(LOCK_FILE).exists() {
+            self.install(&dir).await?;
+        }
+
+        ready.insert(language, dir.clone());
+        Ok(dir)
+    }
+
+    /// 준비된 언어 팩의 lockfile 내용. 오프라인이라 설치하지 않았으면 빈 문자열
+    pub async fn lock_contents(&self, language: Language) -> Result<String> {
+        let dir = self.ensure(language).await?;
+        Ok(fs::read_to_string(dir.join(LOCK_FILE)).unwrap_or_default())
+    }
+
+    async fn install(&self, dir: &Path) -> Result<()> {
+        let mut command = tokio::process::Command::new("codeql");
+        command.arg("pack").arg("install");
+        if let Some(packs) = self.additional_packs_arg() {
+            command.arg(packs);
+        }
+        command.arg(dir);
+        let output = output_with_timeout(command, "codeql pack install", Some(INSTALL_TIMEOUT)).await?;
+        if !output.status.success() {
+            return Err(AppError::CodeQLError(format!(
+                "codeql pack install failed for {}: {}",
+                dir.display(),
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(())
+    }
+
+    /// `--additional-packs=<a>:<b>` 인자, 추가 디렉터리가 없으면 None
+    pub fn additional_packs_arg(&self) -> Option<String> {
+        if self.additional_packs.is_empty() {
+            return None;
+        }
+        let joined = std::env::join_paths(&self.additional_packs).ok()?;
+        Some(format!("--additional-packs={}", joined.to_string_lossy()))
+    }
+}
+
+/// 다른 프로세스가 같은 팩을 준비하는 동안 기다렸다가 배타 잠금을 잡음. 반환된 파일이 드롭되면 풀림
+async fn lock_file(path: PathBuf) -> Result<fs::File> {
+    let file = fs::OpenOptions::new().create(true).truncate(false).write(true).open(&path)?;
+    tokio::task::spawn_blocking(move || file.lock().map(|()| file))
+        .await
+        .map_err(|e| AppError::CodeQLError(format!("Failed to lock {}: {}", path.display(), e)))?
+        .map_err(AppError::from)
+}
+
+fn qlpack_yml(language: Language) -> String {
+    format!(r#"name: codeql-agent/{language}-queries
+version: 0.0.1
+dependencies:
+  codeql/{language}-all: "*"
+"#)
+}
+
+/// 팩 안에 쓴 쿼리 파일. 드롭되면 지움
+pub struct PackQuery {
+    path: PathBuf,
+}
+
+impl PackQuery {
+    pub fn write(pack_dir: &Path, name: &str, query: &str) -> Result<Self> {
+        let path = pack_dir.join("queries").join(format!("{}.ql", name));
+        fs::write(&path, query)?;
+        Ok(PackQuery { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PackQuery {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::workspace::test_dir;
+
+    #[tokio::test]
+    async fn test_offline_pack_is_created_once() {
+        let root = test_dir("query_packs");
+        let packs = QueryPacks::new(root.path()).with_offline(true);
+
+        let dir = packs.ensure(Language::Java).await.unwrap();
+        let qlpack = fs::read_to_string(dir.join("qlpack.yml")).unwrap();
+        assert!(qlpack.contains("dependencies:\n  codeql/java-all: \"*\""));
+        assert!(!qlpack.contains("libraryPathDependencies"));
+
+        // 이전 세션에서 만든 lockfile은 그대로 재사용
+        fs::write(dir.join(LOCK_FILE), "lockVersion: 1.0.0\n").unwrap();
+        let again = QueryPacks::new(root.path()).with_offline(true).ensure(Language::Java).await.unwrap();
+        assert_eq!(dir, again);
+        assert!(dir.join(LOCK_FILE).exists());
+        assert_eq!(packs.lock_contents(Language::Java).await.unwrap(), "lockVersion: 1.0.0\n");
+
+        // 다른 프로세스가 잠금을 잡고 있으면 풀릴 때까지 기다림
+        let held = fs::File::open(root.join("java-queries.lock")).unwrap();
+        held.lock().unwrap();
+        let waiting = tokio::spawn({
+            let root = root.path().to_path_buf();
+            async move { QueryPacks::new(root).with_offline(true).ensure(Language::Java).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!waiting.is_finished());
+        drop(held);
+        assert_eq!(waiting.await.unwrap().unwrap(), dir);
+
+        let query = PackQuery::write(&dir, "query_1", "import java\nselect 1").unwrap();
+        let query_path = query.path().to_path_buf();
+        assert!(query_path.exists());
+        drop(query);
+        assert!(!query_path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_additional_packs_arg() {
+        assert_eq!(QueryPacks::new("/tmp").additional_packs_arg(), None);
+        let packs = QueryPacks::new("/tmp")
+            .with_additional_packs(vec![PathBuf::from("/opt/codeql-repo"), PathBuf::from("/home/a/.codeql/packages")]);
+        assert_eq!(
+            packs.additional_packs_arg().as_deref(),
+            Some("--additional-packs=/opt/codeql-repo:/home/a/.codeql/packages")
+        );
+    }
+}
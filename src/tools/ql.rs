@@ -0,0 +1,230 @@
+// ql.rs - QL 쿼리 템플릿 빌더
+//
+// 파일명, 변수명 같은 값은 모델이 넘기는 경우가 많아서 format!으로 그대로 넣으면
+// 따옴표나 `%` 하나로 쿼리가 깨지거나 다른 쿼리로 바뀔 수 있습니다.
+// 템플릿의 `{{이름}}` 자리에 값을 넣을 때 항상 이스케이프/검증을 거치게 합니다.
+
+use std::collections::HashMap;
+use crate::error::{AppError, Result};
+
+/// `{{이름}}` 자리표시자를 가진 QL 템플릿
+///
+/// ```
+/// use codeql_agent::tools::ql::QlTemplate;
+///
+/// let query = QlTemplate::new("where v.getName() = {{varname}} and v.getLocation().getStartLine() = {{line}}")
+///     .ident("varname", "p")
+///     .int("line", 362)
+///     .build()
+///     .unwrap();
+/// assert_eq!(query, r#"where v.getName() = "p" and v.getLocation().getStartLine() = 362"#);
+/// ```
+pub struct QlTemplate<'a> {
+    template: &'a str,
+    values: HashMap<&'a str, String>,
+    error: Option<AppError>,
+}
+
+impl<'a> QlTemplate<'a> {
+    pub fn new(template: &'a str) -> Self {
+        QlTemplate {
+            template,
+            values: HashMap::new(),
+            error: None,
+        }
+    }
+
+    /// 임의의 문자열을 QL 문자열 리터럴(따옴표 포함)로 넣음
+    pub fn string(mut self, name: &'a str, value: &str) -> Self {
+        match string_literal(value) {
+            Ok(literal) => {
+                self.values.insert(name, literal);
+            }
+            Err(e) => self.fail(e),
+        }
+        self
+    }
+
+    /// 식별자(변수명, 함수명)를 검증한 뒤 문자열 리터럴로 넣음
+    /// `ns::name`, `pkg.Class.name` 같은 한정 이름도 허용
+    pub fn ident(self, name: &'a str, value: &str) -> Self {
+        if is_identifier(value) {
+            self.string(name, value)
+        } else {
+            let mut template = self;
+            template.fail(AppError::InvalidArgument(format!("Invalid identifier: {:?}", value)));
+            template
+        }
+    }
+
+    /// `matches()`에 쓸 "값을 포함" 패턴. 값 안의 `%`, `_`는 와일드카드가 아닌 문자로 취급
+    pub fn contains(self, name: &'a str, value: &str) -> Self {
+        let pattern = format!("%{}%", like_escape(value));
+        self.string(name, &pattern)
+    }
+
+    /// `matches()`에 쓸 "값으로 시작" 패턴
+    pub fn starts_with(self, name: &'a str, value: &str) -> Self {
+        let pattern = format!("{}%", like_escape(value));
+        self.string(name, &pattern)
+    }
+
+    pub fn int(mut self, name: &'a str, value: impl Into<i64>) -> Self {
+        self.values.insert(name, value.into().to_string());
+        self
+    }
+
+    /// 이미 QlTemplate로 만든 조각 등 신뢰할 수 있는 QL 코드를 그대로 넣음
+    pub fn raw(mut self, name: &'a str, fragment: impl Into<String>) -> Self {
+        self.values.insert(name, fragment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<String> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        let mut query = String::with_capacity(self.template.len());
+        let mut rest = self.template;
+        while let Some(start) = rest.find("{{") {
+            query.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| {
+                AppError::CodeQLError("Unterminated placeholder in query template".to_string())
+            })?;
+            let name = after[..end].trim();
+            let value = self.values.get(name).ok_or_else(|| {
+                AppError::CodeQLError(format!("No value for query placeholder {{{{{}}}}}", name))
+            })?;
+            query.push_str(value);
+            rest = &after[end + 2..];
+        }
+        query.push_str(rest);
+        Ok(query)
+    }
+
+    // 첫 번째 에러만 기억했다가 build에서 반환
+    fn fail(&mut self, error: AppError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+}
+
+/// QL 문자열 리터럴로 변환 (양쪽 따옴표 포함)
+pub fn string_literal(value: &str) -> Result<String> {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => {
+                return Err(AppError::InvalidArgument(format!(
+                    "Control character {:?} is not allowed in query strings",
+                    c
+                )));
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    Ok(literal)
+}
+
+/// `matches()` 패턴에서 와일드카드로 해석되는 문자를 이스케이프
+pub fn like_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `::` 나 `.`로 구분된 식별자인지 검사 (C++ 소멸자의 `~`, JS의 `$` 허용)
+pub fn is_identifier(value: &str) -> bool {
+    !value.is_empty()
+        && value.replace("::", ".").split('.').all(|segment| {
+            let name = segment.strip_prefix('~').unwrap_or(segment);
+            let mut chars = name.chars();
+            match chars.next() {
+                Some(c) if c.is_alphabetic() || c == '_' || c == '$' => {}
+                _ => return false,
+            }
+            chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_escapes_quotes() {
+        // 따옴표로 문자열을 끝내고 조건을 추가하려는 입력
+        let query = QlTemplate::new("a.getFile().getRelativePath() = {{filename}}")
+            .string("filename", r#"src/var.c" or any()"#)
+            .build()
+            .unwrap();
+        assert_eq!(query, r#"a.getFile().getRelativePath() = "src/var.c\" or any()""#);
+    }
+
+    #[test]
+    fn test_string_escapes_backslash_and_newline() {
+        let literal = string_literal("C:\\src\\a.c\nimport java").unwrap();
+        assert_eq!(literal, r#""C:\\src\\a.c\nimport java""#);
+        assert!(string_literal("a\0b").is_err());
+    }
+
+    #[test]
+    fn test_contains_escapes_wildcards() {
+        let query = QlTemplate::new("f.getQualifiedName().matches({{pattern}})")
+            .contains("pattern", "100%_done")
+            .build()
+            .unwrap();
+        assert_eq!(query, r#"f.getQualifiedName().matches("%100\\%\\_done%")"#);
+    }
+
+    #[test]
+    fn test_ident_validation() {
+        assert!(is_identifier("file2memory_range"));
+        assert!(is_identifier("std::vector::push_back"));
+        assert!(is_identifier("Foo::~Foo"));
+        assert!(is_identifier("com.example.Foo.bar"));
+        assert!(is_identifier("$scope"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("p\" or any() or \""));
+        assert!(!is_identifier("a b"));
+        assert!(!is_identifier("1abc"));
+        assert!(!is_identifier("a::"));
+
+        let result = QlTemplate::new("v.getName() = {{varname}}")
+            .ident("varname", "x\") or exists(Function f | ")
+            .build();
+        assert!(matches!(result, Err(AppError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_missing_placeholder_value() {
+        let result = QlTemplate::new("select {{missing}}").build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_values_are_not_reexpanded() {
+        // 값 안의 {{...}}는 다시 치환되지 않아야 함
+        let query = QlTemplate::new("{{a}} {{b}}")
+            .string("a", "{{b}}")
+            .int("b", 1)
+            .build()
+            .unwrap();
+        assert_eq!(query, r#""{{b}}" 1"#);
+    }
+}
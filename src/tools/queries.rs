@@ -0,0 +1,1743 @@
+// queries.rs - 언어별 CodeQL 쿼리 템플릿
+//
+// CodeQLAnalyzer의 각 기능은 언어마다 다른 QL 클래스를 쓰지만
+// select 하는 컬럼 이름은 같게 맞춰서 결과 파싱은 공통으로 처리합니다.
+
+use serde::Deserialize;
+use crate::error::{AppError, Result};
+use crate::tools::callgraph::{CallDirection, MAX_DEPTH};
+use crate::tools::language::Language;
+use crate::tools::ql::QlTemplate;
+use crate::tools::symbols::SymbolKind;
+
+fn unsupported(language: Language, operation: &str) -> AppError {
+    AppError::UnsupportedLanguage(format!("{} is not supported for {}", operation, language))
+}
+
+// filename이 주어지면 `file`의 경로가 같아야 한다는 조건
+fn file_filter(file: &str, filename: Option<&str>) -> Result<String> {
+    match filename {
+        Some(filename) => QlTemplate::new("{{file}}.getRelativePath() = {{filename}} and")
+            .raw("file", file)
+            .string("filename", filename)
+            .build(),
+        None => Ok(String::new()),
+    }
+}
+
+// column이 주어지면 `expr`의 범위가 (line, column)을 포함해야 한다는 조건
+fn column_filter(expr: &str, line: u32, column: Option<u32>) -> Result<String> {
+    match column {
+        Some(column) => QlTemplate::new(
+            "{{expr}}.getLocation().getStartColumn() <= {{column}} and \
+             ({{expr}}.getLocation().getEndLine() > {{line}} or {{expr}}.getLocation().getEndColumn() >= {{column}}) and",
+        )
+        .raw("expr", expr)
+        .int("line", line)
+        .int("column", column)
+        .build(),
+        None => Ok(String::new()),
+    }
+}
+
+/// 해당 위치에서 사용된 변수의 정의 위치
+/// 컬럼: filename, startline, endline, column(접근 위치), kind, scope, shadows
+///
+/// 같은 이름의 후보를 구분할 수 있도록 kind(local/parameter/global/member 등),
+/// scope(함수나 클래스), shadows(이 변수가 가리는 바깥 변수, "; "로 구분)를 함께 반환
+pub fn var_definitions(language: Language, filename: &str, line: u32, column: Option<u32>, varname: &str) -> Result<String> {
+    let query = match language {
+        Language::Cpp => r#"
+        import cpp
+
+        string relativePath(Location l) {
+            if exists(l.getFile().getRelativePath())
+            then result = l.getFile().getRelativePath()
+            else result = l.getFile().getBaseName()
+        }
+
+        string kindOf(Variable v) {
+            if v instanceof Parameter then result = "parameter"
+            else if v instanceof LocalVariable then (if v.isStatic() then result = "static local" else result = "local")
+            else if v instanceof MemberVariable then (if v.isStatic() then result = "static member" else result = "member")
+            else if v.isStatic() then result = "static global"
+            else result = "global"
+        }
+
+        string scopeOf(Variable v) {
+            if exists(v.(LocalScopeVariable).getFunction())
+            then result = v.(LocalScopeVariable).getFunction().getQualifiedName()
+            else if v instanceof MemberVariable
+            then result = v.(MemberVariable).getDeclaringType().getQualifiedName()
+            else result = ""
+        }
+
+        // 지역 변수나 파라미터가 가리는 같은 이름의 전역, 멤버, 바깥 블록 변수
+        predicate hides(LocalScopeVariable v, Variable o) {
+            o != v and o.getName() = v.getName() and
+            (
+                o instanceof GlobalOrNamespaceVariable
+                or
+                o.(MemberVariable).getDeclaringType() = v.getFunction().getDeclaringType()
+                or
+                o.(Parameter).getFunction() = v.getFunction()
+                or
+                o.(LocalVariable).getParentScope() = v.getParentScope().(Stmt).getParentStmt+()
+            )
+        }
+
+        from VariableAccess a, Variable v
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getTarget() = v and
+        v.getName() = {{varname}}
+        select
+        v.getFile().getRelativePath() as filename,
+        v.getLocation().getStartLine() as startline,
+        v.getLocation().getEndLine() as endline,
+        a.getLocation().getStartColumn() as column,
+        kindOf(v) as kind,
+        scopeOf(v) as scope,
+        concat(Variable o | hides(v, o) |
+            kindOf(o) + " " + relativePath(o.getLocation()) + ":" + o.getLocation().getStartLine().toString(), "; "
+        ) as shadows
+        "#,
+        Language::Java => r#"
+        import java
+
+        string kindOf(Variable v) {
+            if v instanceof Parameter then result = "parameter"
+            else if v instanceof LocalVariableDecl then result = "local"
+            else if v.(Field).isStatic() then result = "static field"
+            else result = "field"
+        }
+
+        string scopeOf(Variable v) {
+            if exists(v.(LocalScopeVariable).getCallable())
+            then result = v.(LocalScopeVariable).getCallable().getQualifiedName()
+            else if v instanceof Field
+            then result = v.(Field).getDeclaringType().getQualifiedName()
+            else result = ""
+        }
+
+        // 지역 변수나 파라미터가 가리는 같은 클래스의 필드
+        predicate hides(LocalScopeVariable v, Field o) {
+            o.getName() = v.getName() and
+            o.getDeclaringType() = v.getCallable().getDeclaringType()
+        }
+
+        from VarAccess a, Variable v
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getVariable() = v and
+        v.getName() = {{varname}}
+        select
+        v.getFile().getRelativePath() as filename,
+        v.getLocation().getStartLine() as startline,
+        v.getLocation().getEndLine() as endline,
+        a.getLocation().getStartColumn() as column,
+        kindOf(v) as kind,
+        scopeOf(v) as scope,
+        concat(Field o | hides(v, o) |
+            "field " + o.getFile().getBaseName() + ":" + o.getLocation().getStartLine().toString(), "; "
+        ) as shadows
+        "#,
+        Language::Python => r#"
+        import python
+
+        string kindOf(Variable v) {
+            if v instanceof LocalVariable then result = "local"
+            else if v instanceof GlobalVariable then result = "global"
+            else result = "variable"
+        }
+
+        from Name a, Variable v, Name d
+        where
+        a.getLocation().getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getVariable() = v and
+        v.getId() = {{varname}} and
+        d = min(Name n | n.defines(v) | n order by n.getLocation().getStartLine())
+        select
+        d.getLocation().getFile().getRelativePath() as filename,
+        d.getLocation().getStartLine() as startline,
+        d.getLocation().getEndLine() as endline,
+        a.getLocation().getStartColumn() as column,
+        kindOf(v) as kind,
+        v.getScope().getName() as scope,
+        concat(GlobalVariable o |
+            v instanceof LocalVariable and o.getId() = v.getId() and o.getScope() = v.getScope().getEnclosingModule()
+        |
+            "global", "; "
+        ) as shadows
+        "#,
+        Language::JavaScript => r#"
+        import javascript
+
+        string kindOf(Variable v) {
+            if v instanceof LocalVariable then result = "local"
+            else if v instanceof GlobalVariable then result = "global"
+            else result = "variable"
+        }
+
+        from VarRef a, Variable v, VarDecl d
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getVariable() = v and
+        v.getName() = {{varname}} and
+        d = min(VarDecl n | n = v.getADeclaration() | n order by n.getLocation().getStartLine())
+        select
+        d.getFile().getRelativePath() as filename,
+        d.getLocation().getStartLine() as startline,
+        d.getLocation().getEndLine() as endline,
+        a.getLocation().getStartColumn() as column,
+        kindOf(v) as kind,
+        v.getScope().toString() as scope,
+        concat(Variable o | o != v and o.getName() = v.getName() and o.getScope() = v.getScope().getOuterScope+() |
+            kindOf(o) + " " + o.getScope().toString(), "; "
+        ) as shadows
+        "#,
+        Language::CSharp => r#"
+        import csharp
+
+        string kindOf(Variable v) {
+            if v instanceof Parameter then result = "parameter"
+            else if v instanceof LocalVariable then result = "local"
+            else if v.(Field).isStatic() then result = "static field"
+            else result = "field"
+        }
+
+        string scopeOf(Variable v) {
+            if exists(v.(LocalScopeVariable).getCallable())
+            then result = v.(LocalScopeVariable).getCallable().getName()
+            else if v instanceof Field
+            then result = v.(Field).getDeclaringType().getName()
+            else result = ""
+        }
+
+        predicate hides(LocalScopeVariable v, Field o) {
+            o.getName() = v.getName() and
+            o.getDeclaringType() = v.getCallable().getDeclaringType()
+        }
+
+        from VariableAccess a, Variable v
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getTarget() = v and
+        v.getName() = {{varname}}
+        select
+        v.getFile().getRelativePath() as filename,
+        v.getLocation().getStartLine() as startline,
+        v.getLocation().getEndLine() as endline,
+        a.getLocation().getStartColumn() as column,
+        kindOf(v) as kind,
+        scopeOf(v) as scope,
+        concat(Field o | hides(v, o) |
+            "field " + o.getFile().getBaseName() + ":" + o.getLocation().getStartLine().toString(), "; "
+        ) as shadows
+        "#,
+        Language::Go | Language::Ruby | Language::Swift => return Err(unsupported(language, "variable definitions")),
+    };
+    QlTemplate::new(query)
+        .string("filename", filename)
+        .int("line", line)
+        .raw("column", column_filter("a", line, column)?)
+        .ident("varname", varname)
+        .build()
+}
+
+/// 해당 위치에서 사용된 변수의 모든 접근
+/// 컬럼: filename, line, column, kind(read/write/address/argument), function
+pub fn var_refs(language: Language, filename: &str, line: u32, column: Option<u32>, varname: &str) -> Result<String> {
+    let query = match language {
+        Language::Cpp => r#"
+        import cpp
+
+        from VariableAccess a, Variable v, VariableAccess ref, string kind, string function
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getTarget() = v and
+        v.getName() = {{varname}} and
+        ref.getTarget() = v and
+        (
+            (ref.isModified() and kind = "write")
+            or
+            (ref.getParent() instanceof AddressOfExpr and kind = "address")
+            or
+            (ref = any(Call c).getAnArgument() and kind = "argument")
+            or
+            (
+                // x += 1, x++ 는 값을 읽은 뒤 쓰므로 read와 write 둘 다
+                (
+                    not ref.isModified() or
+                    ref = any(AssignOperation op).getLValue() or
+                    ref = any(CrementOperation op).getOperand()
+                ) and
+                not ref.getParent() instanceof AddressOfExpr and
+                kind = "read"
+            )
+        ) and
+        (
+            function = ref.getEnclosingFunction().getQualifiedName()
+            or
+            (not exists(ref.getEnclosingFunction()) and function = "")
+        )
+        select
+        ref.getFile().getRelativePath() as filename,
+        ref.getLocation().getStartLine() as line,
+        ref.getLocation().getStartColumn() as column,
+        kind,
+        function
+        "#,
+        Language::Java => r#"
+        import java
+
+        from VarAccess a, Variable v, VarAccess ref, string kind, string function
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getVariable() = v and
+        v.getName() = {{varname}} and
+        ref.getVariable() = v and
+        (
+            (ref.isVarWrite() and kind = "write")
+            or
+            (ref.isVarRead() and kind = "read")
+            or
+            (ref = any(Call c).getAnArgument() and kind = "argument")
+        ) and
+        (
+            function = ref.getEnclosingCallable().getQualifiedName()
+            or
+            (not exists(ref.getEnclosingCallable()) and function = "")
+        )
+        select
+        ref.getFile().getRelativePath() as filename,
+        ref.getLocation().getStartLine() as line,
+        ref.getLocation().getStartColumn() as column,
+        kind,
+        function
+        "#,
+        Language::Python => r#"
+        import python
+
+        from Name a, Variable v, Name ref, string kind, string function
+        where
+        a.getLocation().getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getVariable() = v and
+        v.getId() = {{varname}} and
+        ref.getVariable() = v and
+        (
+            (ref.defines(v) and kind = "write")
+            or
+            (ref.uses(v) and kind = "read")
+            or
+            (ref = any(Call c).getAnArg() and kind = "argument")
+        ) and
+        (
+            function = ref.getScope().(Function).getQualifiedName()
+            or
+            (not ref.getScope() instanceof Function and function = "")
+        )
+        select
+        ref.getLocation().getFile().getRelativePath() as filename,
+        ref.getLocation().getStartLine() as line,
+        ref.getLocation().getStartColumn() as column,
+        kind,
+        function
+        "#,
+        Language::JavaScript => r#"
+        import javascript
+
+        from VarRef a, Variable v, VarRef ref, string kind, string function
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getVariable() = v and
+        v.getName() = {{varname}} and
+        ref.getVariable() = v and
+        (
+            (ref instanceof LValue and kind = "write")
+            or
+            (ref instanceof RValue and kind = "read")
+            or
+            (ref = any(InvokeExpr c).getAnArgument() and kind = "argument")
+        ) and
+        (
+            function = ref.getEnclosingFunction().getName()
+            or
+            (not exists(ref.getEnclosingFunction().getName()) and function = "")
+        )
+        select
+        ref.getFile().getRelativePath() as filename,
+        ref.getLocation().getStartLine() as line,
+        ref.getLocation().getStartColumn() as column,
+        kind,
+        function
+        "#,
+        Language::CSharp => r#"
+        import csharp
+
+        from VariableAccess a, Variable v, VariableAccess ref, string kind, string function
+        where
+        a.getFile().getRelativePath() = {{filename}} and
+        a.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        a.getTarget() = v and
+        v.getName() = {{varname}} and
+        ref.getTarget() = v and
+        (
+            (ref instanceof VariableWrite and kind = "write")
+            or
+            (ref instanceof VariableRead and kind = "read")
+            or
+            (ref.getParent() instanceof AddressOfExpr and kind = "address")
+            or
+            (ref = any(Call c).getAnArgument() and kind = "argument")
+        ) and
+        (
+            function = ref.getEnclosingCallable().getQualifiedName()
+            or
+            (not exists(ref.getEnclosingCallable()) and function = "")
+        )
+        select
+        ref.getFile().getRelativePath() as filename,
+        ref.getLocation().getStartLine() as line,
+        ref.getLocation().getStartColumn() as column,
+        kind,
+        function
+        "#,
+        Language::Go | Language::Ruby | Language::Swift => return Err(unsupported(language, "variable references")),
+    };
+    QlTemplate::new(query)
+        .string("filename", filename)
+        .int("line", line)
+        .raw("column", column_filter("a", line, column)?)
+        .ident("varname", varname)
+        .build()
+}
+
+/// 해당 위치에서 호출된 함수의 정의
+/// 컬럼: qualified_name, filename, startline, endline, is_virtual
+pub fn function_implementation(language: Language, filename: &str, line: u32, column: Option<u32>, funcname: &str) -> Result<String> {
+    let query = match language {
+        Language::Cpp => r#"
+        import cpp
+
+        from FunctionCall fc, Function target, string is_virtual
+        where
+        fc.getFile().getRelativePath() = {{filename}} and
+        fc.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        target = fc.getTarget() and
+        (
+            target.getName() = {{funcname}}
+            or target.getQualifiedName().matches({{funcname_pattern}})
+        ) and
+        (
+            (target instanceof VirtualFunction and is_virtual = "true")
+            or
+            (not target instanceof VirtualFunction and is_virtual = "false")
+        )
+        select
+        target.getQualifiedName() as qualified_name,
+        target.getFile().getRelativePath() as filename,
+        target.getLocation().getStartLine() as startline,
+        target.getLocation().getEndLine() as endline,
+        is_virtual
+        "#,
+        Language::Java => r#"
+        import java
+
+        from Call fc, Callable target, string is_virtual
+        where
+        fc.getFile().getRelativePath() = {{filename}} and
+        fc.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        target = fc.getCallee().getSourceDeclaration() and
+        (
+            target.getName() = {{funcname}}
+            or target.getQualifiedName().matches({{funcname_pattern}})
+        ) and
+        (
+            (
+                target instanceof Method and
+                not target.isStatic() and not target.isPrivate() and not target.isFinal() and
+                is_virtual = "true"
+            )
+            or
+            (
+                (not target instanceof Method or target.isStatic() or target.isPrivate() or target.isFinal()) and
+                is_virtual = "false"
+            )
+        )
+        select
+        target.getQualifiedName() as qualified_name,
+        target.getFile().getRelativePath() as filename,
+        target.getLocation().getStartLine() as startline,
+        target.getBody().getLocation().getEndLine() as endline,
+        is_virtual
+        "#,
+        Language::Python => r#"
+        import python
+
+        from Call fc, FunctionValue fv, Function target, string is_virtual
+        where
+        fc.getLocation().getFile().getRelativePath() = {{filename}} and
+        fc.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        fc.getFunc().pointsTo(fv) and
+        target = fv.getScope() and
+        (
+            target.getName() = {{funcname}}
+            or target.getQualifiedName().matches({{funcname_pattern}})
+        ) and
+        is_virtual = "false"
+        select
+        target.getQualifiedName() as qualified_name,
+        target.getLocation().getFile().getRelativePath() as filename,
+        target.getLocation().getStartLine() as startline,
+        max(Stmt s | s.getScope() = target | s.getLocation().getEndLine()) as endline,
+        is_virtual
+        "#,
+        Language::JavaScript => r#"
+        import javascript
+
+        from InvokeExpr fc, Function target, string is_virtual
+        where
+        fc.getFile().getRelativePath() = {{filename}} and
+        fc.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        target = fc.getResolvedCallee() and
+        target.getName() = {{funcname}} and
+        is_virtual = "false"
+        select
+        target.getName() as qualified_name,
+        target.getFile().getRelativePath() as filename,
+        target.getLocation().getStartLine() as startline,
+        target.getLocation().getEndLine() as endline,
+        is_virtual
+        "#,
+        Language::CSharp => r#"
+        import csharp
+
+        from Call fc, Callable target, string is_virtual
+        where
+        fc.getFile().getRelativePath() = {{filename}} and
+        fc.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        target = fc.getTarget().getUnboundDeclaration() and
+        (
+            target.getName() = {{funcname}}
+            or target.getQualifiedName().matches({{funcname_pattern}})
+        ) and
+        (
+            (
+                exists(Method m | m = target and (m.isVirtual() or m.isAbstract() or m.isOverride())) and
+                is_virtual = "true"
+            )
+            or
+            (
+                not exists(Method m | m = target and (m.isVirtual() or m.isAbstract() or m.isOverride())) and
+                is_virtual = "false"
+            )
+        )
+        select
+        target.getQualifiedName() as qualified_name,
+        target.getFile().getRelativePath() as filename,
+        target.getLocation().getStartLine() as startline,
+        target.getBody().getLocation().getEndLine() as endline,
+        is_virtual
+        "#,
+        Language::Go | Language::Ruby | Language::Swift => return Err(unsupported(language, "function implementations")),
+    };
+    QlTemplate::new(query)
+        .string("filename", filename)
+        .int("line", line)
+        .raw("column", column_filter("fc", line, column)?)
+        .ident("funcname", funcname)
+        .contains("funcname_pattern", funcname)
+        .build()
+}
+
+/// 가상 함수를 오버라이드한 함수들
+/// 컬럼: qualified_name, filename, startline, endline
+pub fn function_overrides(language: Language, qualified_name: &str) -> Result<String> {
+    let query = match language {
+        Language::Cpp => r#"
+        import cpp
+
+        from VirtualFunction base, Function override
+        where
+        base.getQualifiedName() = {{qualified_name}} and
+        override = base.getAnOverridingFunction()
+        select
+        override.getQualifiedName() as qualified_name,
+        override.getFile().getRelativePath() as filename,
+        override.getLocation().getStartLine() as startline,
+        override.getLocation().getEndLine() as endline
+        "#,
+        Language::Java => r#"
+        import java
+
+        from Method base, Method override
+        where
+        base.getQualifiedName() = {{qualified_name}} and
+        override.overrides+(base) and
+        exists(override.getBody())
+        select
+        override.getQualifiedName() as qualified_name,
+        override.getFile().getRelativePath() as filename,
+        override.getLocation().getStartLine() as startline,
+        override.getBody().getLocation().getEndLine() as endline
+        "#,
+        Language::CSharp => r#"
+        import csharp
+
+        from Method base, Method override
+        where
+        base.getQualifiedName() = {{qualified_name}} and
+        (override.getOverridee+() = base or override.getImplementee() = base) and
+        override.hasBody()
+        select
+        override.getQualifiedName() as qualified_name,
+        override.getFile().getRelativePath() as filename,
+        override.getLocation().getStartLine() as startline,
+        override.getBody().getLocation().getEndLine() as endline
+        "#,
+        Language::Python | Language::JavaScript | Language::Go | Language::Ruby | Language::Swift => {
+            return Err(unsupported(language, "function overrides"))
+        }
+    };
+    // 이전 쿼리 결과에서 온 이름이라 템플릿 인자(`Foo<int>::f`)가 있을 수 있어 식별자 검사는 하지 않음
+    QlTemplate::new(query)
+        .string("qualified_name", qualified_name)
+        .build()
+}
+
+/// 함수를 호출하는 모든 곳과 호출한 함수
+/// 컬럼: caller, filename, line, startline, endline
+pub fn function_refs(language: Language, filename: Option<&str>, funcname: &str) -> Result<String> {
+    let (query, file) = match language {
+        Language::Cpp => (r#"
+        import cpp
+
+        from FunctionCall fc, Function target, Function caller
+        where
+        target = fc.getTarget() and
+        target.getName() = {{funcname}} and
+        {{file_filter}}
+        caller = fc.getEnclosingFunction()
+        select
+        caller.getQualifiedName() as caller,
+        fc.getFile().getRelativePath() as filename,
+        fc.getLocation().getStartLine() as line,
+        caller.getLocation().getStartLine() as startline,
+        caller.getBlock().getLocation().getEndLine() as endline
+        "#, "target.getFile()"),
+        Language::Java => (r#"
+        import java
+
+        from Call fc, Callable target, Callable caller
+        where
+        target = fc.getCallee().getSourceDeclaration() and
+        target.getName() = {{funcname}} and
+        {{file_filter}}
+        caller = fc.getEnclosingCallable()
+        select
+        caller.getQualifiedName() as caller,
+        fc.getFile().getRelativePath() as filename,
+        fc.getLocation().getStartLine() as line,
+        caller.getLocation().getStartLine() as startline,
+        caller.getBody().getLocation().getEndLine() as endline
+        "#, "target.getFile()"),
+        Language::Python => (r#"
+        import python
+
+        from Call fc, FunctionValue fv, Function target, Function caller
+        where
+        fc.getFunc().pointsTo(fv) and
+        target = fv.getScope() and
+        target.getName() = {{funcname}} and
+        {{file_filter}}
+        caller = fc.getScope()
+        select
+        caller.getQualifiedName() as caller,
+        fc.getLocation().getFile().getRelativePath() as filename,
+        fc.getLocation().getStartLine() as line,
+        caller.getLocation().getStartLine() as startline,
+        max(Stmt s | s.getScope() = caller | s.getLocation().getEndLine()) as endline
+        "#, "target.getLocation().getFile()"),
+        Language::JavaScript => (r#"
+        import javascript
+
+        from InvokeExpr fc, Function target, Function caller
+        where
+        target = fc.getResolvedCallee() and
+        target.getName() = {{funcname}} and
+        {{file_filter}}
+        caller = fc.getEnclosingFunction()
+        select
+        caller.describe() as caller,
+        fc.getFile().getRelativePath() as filename,
+        fc.getLocation().getStartLine() as line,
+        caller.getLocation().getStartLine() as startline,
+        caller.getLocation().getEndLine() as endline
+        "#, "target.getFile()"),
+        Language::CSharp => (r#"
+        import csharp
+
+        from Call fc, Callable target, Callable caller
+        where
+        target = fc.getTarget().getUnboundDeclaration() and
+        target.getName() = {{funcname}} and
+        {{file_filter}}
+        caller = fc.getEnclosingCallable()
+        select
+        caller.getQualifiedName() as caller,
+        fc.getFile().getRelativePath() as filename,
+        fc.getLocation().getStartLine() as line,
+        caller.getLocation().getStartLine() as startline,
+        caller.getBody().getLocation().getEndLine() as endline
+        "#, "target.getFile()"),
+        Language::Go | Language::Ruby | Language::Swift => return Err(unsupported(language, "function references")),
+    };
+    QlTemplate::new(query)
+        .ident("funcname", funcname)
+        .raw("file_filter", file_filter(file, filename)?)
+        .build()
+}
+
+/// 이름으로 찾은 함수 정의
+/// 컬럼: qualified_name, signature, filename, startline, endline, is_static
+pub fn function_code(language: Language, filename: Option<&str>, funcname: &str) -> Result<String> {
+    let (query, file) = match language {
+        Language::Cpp => (r#"
+        import cpp
+
+        from Function f, string is_static
+        where
+        (
+            f.getName() = {{funcname}}
+            or f.getQualifiedName() = {{funcname}}
+        ) and
+        f.hasDefinition() and
+        {{file_filter}}
+        (
+            (f.isStatic() and is_static = "true")
+            or
+            (not f.isStatic() and is_static = "false")
+        )
+        select
+        f.getQualifiedName() as qualified_name,
+        f.getType().toString() + " " + f.getQualifiedName() + "(" + f.getParameterString() + ")" as signature,
+        f.getDefinitionLocation().getFile().getRelativePath() as filename,
+        f.getDefinitionLocation().getStartLine() as startline,
+        f.getBlock().getLocation().getEndLine() as endline,
+        is_static
+        "#, "f.getDefinitionLocation().getFile()"),
+        Language::Java => (r#"
+        import java
+
+        from Callable f, string is_static
+        where
+        (
+            f.getName() = {{funcname}}
+            or f.getQualifiedName() = {{funcname}}
+        ) and
+        exists(f.getBody()) and
+        {{file_filter}}
+        (
+            (f.isStatic() and is_static = "true")
+            or
+            (not f.isStatic() and is_static = "false")
+        )
+        select
+        f.getQualifiedName() as qualified_name,
+        f.getReturnType().toString() + " " + f.getDeclaringType().getQualifiedName() + "." + f.getSignature() as signature,
+        f.getFile().getRelativePath() as filename,
+        f.getLocation().getStartLine() as startline,
+        f.getBody().getLocation().getEndLine() as endline,
+        is_static
+        "#, "f.getFile()"),
+        Language::Python => (r#"
+        import python
+
+        from Function f, string is_static
+        where
+        (
+            f.getName() = {{funcname}}
+            or f.getQualifiedName() = {{funcname}}
+        ) and
+        {{file_filter}}
+        is_static = "false"
+        select
+        f.getQualifiedName() as qualified_name,
+        "def " + f.getQualifiedName() + "(" + concat(int i | exists(f.getArg(i)) | f.getArgName(i), ", " order by i) + ")" as signature,
+        f.getLocation().getFile().getRelativePath() as filename,
+        f.getLocation().getStartLine() as startline,
+        max(Stmt s | s.getScope() = f | s.getLocation().getEndLine()) as endline,
+        is_static
+        "#, "f.getLocation().getFile()"),
+        Language::JavaScript => (r#"
+        import javascript
+
+        from Function f, string is_static
+        where
+        f.getName() = {{funcname}} and
+        {{file_filter}}
+        is_static = "false"
+        select
+        f.getName() as qualified_name,
+        "function " + f.getName() + "(" + concat(int i | exists(f.getParameter(i)) | f.getParameter(i).(SimpleParameter).getName(), ", " order by i) + ")" as signature,
+        f.getFile().getRelativePath() as filename,
+        f.getLocation().getStartLine() as startline,
+        f.getLocation().getEndLine() as endline,
+        is_static
+        "#, "f.getFile()"),
+        Language::CSharp => (r#"
+        import csharp
+
+        from Callable f, string is_static
+        where
+        (
+            f.getName() = {{funcname}}
+            or f.getQualifiedName() = {{funcname}}
+        ) and
+        f.hasBody() and
+        {{file_filter}}
+        (
+            (f.(Modifiable).isStatic() and is_static = "true")
+            or
+            (not f.(Modifiable).isStatic() and is_static = "false")
+        )
+        select
+        f.getQualifiedName() as qualified_name,
+        f.toStringWithTypes() as signature,
+        f.getFile().getRelativePath() as filename,
+        f.getLocation().getStartLine() as startline,
+        f.getBody().getLocation().getEndLine() as endline,
+        is_static
+        "#, "f.getFile()"),
+        Language::Go | Language::Ruby | Language::Swift => return Err(unsupported(language, "function lookup")),
+    };
+    QlTemplate::new(query)
+        .ident("funcname", funcname)
+        .raw("file_filter", file_filter(file, filename)?)
+        .build()
+}
+
+/// 함수 본문 요약. 함수마다 signature 한 행과 항목마다 한 행
+/// 컬럼: filename, startline(함수 정의 라인), section, name, type, line, endline, detail
+///
+/// section별 의미:
+/// - signature: name = 한정된 이름, type = 시그니처
+/// - parameter: detail = 위치(0부터)
+/// - local: detail = "static" 또는 ""
+/// - call: name = 호출한 함수, detail = "pointer"(함수 포인터 호출) 또는 ""
+/// - return, loop(name = for/while/do/range for), allocation(name = 할당 함수)
+pub fn function_summary(language: Language, filename: Option<&str>, funcname: &str) -> Result<String> {
+    let (query, file) = match language {
+        Language::Cpp => (r#"
+        import cpp
+        import semmle.code.cpp.models.interfaces.Allocation
+
+        string loopKind(Loop l) {
+            if l instanceof ForStmt then result = "for"
+            else if l instanceof WhileStmt then result = "while"
+            else if l instanceof DoStmt then result = "do"
+            else result = "range for"
+        }
+
+        from Function f, string section, string name, string type, int line, int endline, string detail
+        where
+        (
+            f.getName() = {{funcname}}
+            or f.getQualifiedName() = {{funcname}}
+        ) and
+        f.hasDefinition() and
+        {{file_filter}}
+        (
+            (
+                section = "signature" and name = f.getQualifiedName() and
+                type = f.getType().toString() + " " + f.getQualifiedName() + "(" + f.getParameterString() + ")" and
+                line = f.getDefinitionLocation().getStartLine() and endline = f.getBlock().getLocation().getEndLine() and
+                detail = ""
+            )
+            or
+            exists(Parameter p | p = f.getAParameter() |
+                section = "parameter" and name = p.getName() and type = p.getType().toString() and
+                line = p.getLocation().getStartLine() and endline = line and detail = p.getIndex().toString()
+            )
+            or
+            exists(LocalVariable v | v.getFunction() = f |
+                section = "local" and name = v.getName() and type = v.getType().toString() and
+                line = v.getLocation().getStartLine() and endline = line and
+                (if v.isStatic() then detail = "static" else detail = "")
+            )
+            or
+            exists(FunctionCall c | c.getEnclosingFunction() = f |
+                section = "call" and name = c.getTarget().getQualifiedName() and type = "" and
+                line = c.getLocation().getStartLine() and endline = line and detail = ""
+            )
+            or
+            exists(ExprCall c | c.getEnclosingFunction() = f |
+                section = "call" and name = c.getExpr().toString() and type = "" and
+                line = c.getLocation().getStartLine() and endline = line and detail = "pointer"
+            )
+            or
+            exists(ReturnStmt r | r.getEnclosingFunction() = f |
+                section = "return" and name = "" and type = "" and
+                line = r.getLocation().getStartLine() and endline = r.getLocation().getEndLine() and detail = ""
+            )
+            or
+            exists(Loop l | l.getEnclosingFunction() = f |
+                section = "loop" and name = loopKind(l) and type = "" and
+                line = l.getLocation().getStartLine() and endline = l.getLocation().getEndLine() and detail = ""
+            )
+            or
+            exists(AllocationExpr a | a.getEnclosingFunction() = f |
+                section = "allocation" and
+                (if a instanceof FunctionCall then name = a.(FunctionCall).getTarget().getName() else name = "new") and
+                type = a.getType().toString() and
+                line = a.getLocation().getStartLine() and endline = a.getLocation().getEndLine() and detail = ""
+            )
+        )
+        select
+        f.getDefinitionLocation().getFile().getRelativePath() as filename,
+        f.getDefinitionLocation().getStartLine() as startline,
+        section, name, type, line, endline, detail
+        "#, "f.getDefinitionLocation().getFile()"),
+        Language::Java => (r#"
+        import java
+
+        string loopKind(LoopStmt l) {
+            if l instanceof ForStmt then result = "for"
+            else if l instanceof WhileStmt then result = "while"
+            else if l instanceof DoStmt then result = "do"
+            else result = "range for"
+        }
+
+        from Callable f, string section, string name, string type, int line, int endline, string detail
+        where
+        (
+            f.getName() = {{funcname}}
+            or f.getQualifiedName() = {{funcname}}
+        ) and
+        exists(f.getBody()) and
+        {{file_filter}}
+        (
+            (
+                section = "signature" and name = f.getQualifiedName() and
+                type = f.getReturnType().toString() + " " + f.getDeclaringType().getQualifiedName() + "." + f.getSignature() and
+                line = f.getLocation().getStartLine() and endline = f.getBody().getLocation().getEndLine() and
+                detail = ""
+            )
+            or
+            exists(Parameter p | p = f.getAParameter() |
+                section = "parameter" and name = p.getName() and type = p.getType().toString() and
+                line = p.getLocation().getStartLine() and endline = line and detail = p.getPosition().toString()
+            )
+            or
+            exists(LocalVariableDecl v | v.getCallable() = f |
+                section = "local" and name = v.getName() and type = v.getType().toString() and
+                line = v.getLocation().getStartLine() and endline = line and detail = ""
+            )
+            or
+            exists(Call c | c.getEnclosingCallable() = f and not c instanceof ClassInstanceExpr |
+                section = "call" and name = c.getCallee().getQualifiedName() and type = "" and
+                line = c.getLocation().getStartLine() and endline = line and detail = ""
+            )
+            or
+            exists(ReturnStmt r | r.getEnclosingCallable() = f |
+                section = "return" and name = "" and type = "" and
+                line = r.getLocation().getStartLine() and endline = r.getLocation().getEndLine() and detail = ""
+            )
+            or
+            exists(LoopStmt l | l.getEnclosingCallable() = f |
+                section = "loop" and name = loopKind(l) and type = "" and
+                line = l.getLocation().getStartLine() and endline = l.getLocation().getEndLine() and detail = ""
+            )
+            or
+            exists(Expr a | a.getEnclosingCallable() = f and (a instanceof ClassInstanceExpr or a instanceof ArrayCreationExpr) |
+                section = "allocation" and name = "new" and type = a.getType().toString() and
+                line = a.getLocation().getStartLine() and endline = a.getLocation().getEndLine() and detail = ""
+            )
+        )
+        select
+        f.getFile().getRelativePath() as filename,
+        f.getLocation().getStartLine() as startline,
+        section, name, type, line, endline, detail
+        "#, "f.getFile()"),
+        _ => return Err(unsupported(language, "function summaries")),
+    };
+    QlTemplate::new(query)
+        .ident("funcname", funcname)
+        .raw("file_filter", file_filter(file, filename)?)
+        .build()
+}
+
+/// 데이터 흐름의 시작점 또는 끝점
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FlowSpec {
+    /// source: 함수 호출의 반환값, sink: 함수가 return 하는 값
+    ReturnValue { function: String },
+    /// 함수의 index번째 파라미터 (0부터)
+    Parameter { function: String, index: u32 },
+    /// 함수 호출의 index번째 인자. source면 호출이 인자에 써 넣은 값(recv의 버퍼 등)
+    Argument { function: String, index: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlowRole {
+    Source,
+    Sink,
+}
+
+// DataFlow::Node n에 대한 조건
+fn flow_spec_predicate(language: Language, spec: &FlowSpec, role: FlowRole) -> Result<String> {
+    let (template, function, index) = match (language, spec, role) {
+        (Language::Cpp, FlowSpec::ReturnValue { function }, FlowRole::Source) => (
+            "n.asExpr().(Call).getTarget().getName() = {{function}}",
+            function,
+            0,
+        ),
+        (Language::Cpp, FlowSpec::ReturnValue { function }, FlowRole::Sink) => (
+            "exists(ReturnStmt r | r.getEnclosingFunction().getName() = {{function}} and n.asExpr() = r.getExpr())",
+            function,
+            0,
+        ),
+        (Language::Cpp, FlowSpec::Parameter { function, index }, _) => (
+            "exists(Function f | f.getName() = {{function}} and n.asParameter() = f.getParameter({{index}}))",
+            function,
+            *index,
+        ),
+        (Language::Cpp, FlowSpec::Argument { function, index }, FlowRole::Source) => (
+            "exists(Call c | c.getTarget().getName() = {{function}} and n.asDefiningArgument() = c.getArgument({{index}}))",
+            function,
+            *index,
+        ),
+        // 포인터 인자는 가리키는 버퍼 내용까지 sink로 봄
+        (Language::Cpp, FlowSpec::Argument { function, index }, FlowRole::Sink) => (
+            "exists(Call c | c.getTarget().getName() = {{function}} and (n.asExpr() = c.getArgument({{index}}) or n.asIndirectExpr() = c.getArgument({{index}})))",
+            function,
+            *index,
+        ),
+        (Language::Java, FlowSpec::ReturnValue { function }, FlowRole::Source) => (
+            "n.asExpr().(MethodCall).getMethod().getName() = {{function}}",
+            function,
+            0,
+        ),
+        (Language::Java, FlowSpec::ReturnValue { function }, FlowRole::Sink) => (
+            "exists(ReturnStmt r | r.getEnclosingCallable().getName() = {{function}} and n.asExpr() = r.getResult())",
+            function,
+            0,
+        ),
+        (Language::Java, FlowSpec::Parameter { function, index }, _) => (
+            "exists(Callable f | f.getName() = {{function}} and n.asParameter() = f.getParameter({{index}}))",
+            function,
+            *index,
+        ),
+        (Language::Java, FlowSpec::Argument { function, index }, FlowRole::Source) => (
+            "exists(Call c | c.getCallee().getName() = {{function}} and n.(DataFlow::PostUpdateNode).getPreUpdateNode().asExpr() = c.getArgument({{index}}))",
+            function,
+            *index,
+        ),
+        (Language::Java, FlowSpec::Argument { function, index }, FlowRole::Sink) => (
+            "exists(Call c | c.getCallee().getName() = {{function}} and n.asExpr() = c.getArgument({{index}}))",
+            function,
+            *index,
+        ),
+        _ => return Err(unsupported(language, "data flow")),
+    };
+    QlTemplate::new(template)
+        .ident("function", function)
+        .int("index", index)
+        .build()
+}
+
+/// source에서 sink로 가는 taint 흐름을 찾는 path-problem 쿼리
+pub fn dataflow(language: Language, source: &FlowSpec, sink: &FlowSpec) -> Result<String> {
+    let query = match language {
+        Language::Cpp => r#"
+        /**
+         * @kind path-problem
+         */
+        import cpp
+        import semmle.code.cpp.dataflow.new.TaintTracking
+
+        module AgentFlowConfig implements DataFlow::ConfigSig {
+            predicate isSource(DataFlow::Node n) { {{source}} }
+
+            predicate isSink(DataFlow::Node n) { {{sink}} }
+        }
+
+        module AgentFlow = TaintTracking::Global<AgentFlowConfig>;
+
+        import AgentFlow::PathGraph
+
+        from AgentFlow::PathNode source, AgentFlow::PathNode sink
+        where AgentFlow::flowPath(source, sink)
+        select sink.getNode(), source, sink, "Flow from " + source.toString() + " to " + sink.toString()
+        "#,
+        Language::Java => r#"
+        /**
+         * @kind path-problem
+         */
+        import java
+        import semmle.code.java.dataflow.TaintTracking
+
+        module AgentFlowConfig implements DataFlow::ConfigSig {
+            predicate isSource(DataFlow::Node n) { {{source}} }
+
+            predicate isSink(DataFlow::Node n) { {{sink}} }
+        }
+
+        module AgentFlow = TaintTracking::Global<AgentFlowConfig>;
+
+        import AgentFlow::PathGraph
+
+        from AgentFlow::PathNode source, AgentFlow::PathNode sink
+        where AgentFlow::flowPath(source, sink)
+        select sink.getNode(), source, sink, "Flow from " + source.toString() + " to " + sink.toString()
+        "#,
+        _ => return Err(unsupported(language, "data flow")),
+    };
+    QlTemplate::new(query)
+        .raw("source", flow_spec_predicate(language, source, FlowRole::Source)?)
+        .raw("sink", flow_spec_predicate(language, sink, FlowRole::Sink)?)
+        .build()
+}
+
+/// 해당 위치의 매크로 호출과 매크로 정의. 인자마다 한 행
+/// 컬럼: column, head, body, filename, startline, endline, index, argument, expanded,
+/// expression, expression_type (인자가 없으면 index = -1)
+///
+/// expanded는 CodeQL이 기록한 인자의 전개 결과, expression과 expression_type은 호출이 전개된
+/// 최상위 식(없으면 빈 문자열). 시스템 헤더의 매크로는 filename이 빈 문자열
+pub fn macro_invocation(language: Language, filename: &str, line: u32, column: Option<u32>, name: &str) -> Result<String> {
+    let query = match language {
+        Language::Cpp => r#"
+        import cpp
+
+        string relativePath(Location l) {
+            if exists(l.getFile().getRelativePath())
+            then result = l.getFile().getRelativePath()
+            else result = ""
+        }
+
+        from MacroInvocation mi, Macro m, int index, string argument, string expanded, string expression, string expressionType
+        where
+        mi.getFile().getRelativePath() = {{filename}} and
+        mi.getLocation().getStartLine() = {{line}} and
+        {{column}}
+        m = mi.getMacro() and
+        m.getName() = {{name}} and
+        (
+            argument = mi.getUnexpandedArgument(index) and
+            (
+                if exists(mi.getExpandedArgument(index))
+                then expanded = mi.getExpandedArgument(index)
+                else expanded = argument
+            )
+            or
+            (not exists(mi.getUnexpandedArgument(_)) and index = -1 and argument = "" and expanded = "")
+        ) and
+        (
+            if exists(mi.getExpr())
+            then expression = mi.getExpr().toString() and expressionType = mi.getExpr().getType().toString()
+            else (expression = "" and expressionType = "")
+        )
+        select
+        mi.getLocation().getStartColumn() as column,
+        m.getHead() as head,
+        m.getBody() as body,
+        relativePath(m.getLocation()) as filename,
+        m.getLocation().getStartLine() as startline,
+        m.getLocation().getEndLine() as endline,
+        index,
+        argument,
+        expanded,
+        expression,
+        expressionType as expression_type
+        "#,
+        _ => return Err(unsupported(language, "macros")),
+    };
+    QlTemplate::new(query)
+        .string("filename", filename)
+        .int("line", line)
+        .raw("column", column_filter("mi", line, column)?)
+        .ident("name", name)
+        .build()
+}
+
+/// 해당 위치에서 쓰인 타입의 typedef 체인, 정의, 멤버, 베이스 클래스
+/// 컬럼: section(typedef/definition/member/base), owner, name, type, filename, startline, endline, offset, size
+///
+/// typedef는 owner에 체인 순서, definition은 type에 종류(struct/class/union/enum/interface),
+/// base는 type에 "virtual" 또는 "". 알 수 없는 offset, size는 -1
+pub fn type_definition(language: Language, filename: &str, line: u32, column: Option<u32>, typename: &str) -> Result<String> {
+    // C++는 TypeMention, Java는 TypeAccess의 위치로 찾음
+    let (query, mention) = match language {
+        Language::Cpp => (r#"
+        import cpp
+
+        string relativePath(Location l) {
+            if exists(l.getFile().getRelativePath())
+            then result = l.getFile().getRelativePath()
+            else result = ""
+        }
+
+        int sizeOf(Type t) {
+            if exists(t.getSize()) then result = t.getSize() else result = -1
+        }
+
+        predicate mentioned(Type t) {
+            exists(TypeMention m |
+                m.getFile().getRelativePath() = {{filename}} and
+                m.getLocation().getStartLine() = {{line}} and
+                {{column}}
+                t = m.getMentionedType() and
+                t.getName() = {{typename}}
+            )
+        }
+
+        predicate candidate(UserType t) {
+            t.getName() = {{typename}}
+        }
+
+        // 해당 라인에서 찾지 못하면 같은 이름의 타입. 정의가 있는 타입이 있으면 전방 선언만 있는 것은 뺌
+        // (순위와 개수 제한은 find_type_definition에서)
+        Type start() {
+            mentioned(result)
+            or
+            not mentioned(_) and candidate(result) and
+            (result.(UserType).hasDefinition() or not exists(UserType t | candidate(t) and t.hasDefinition()))
+        }
+
+        // typedef와 const, 포인터 등을 벗겨 나가는 체인
+        Type chain(int step) {
+            step = 0 and result = start()
+            or
+            exists(Type prev | prev = chain(step - 1) and step < 16 |
+                result = prev.(TypedefType).getBaseType() or result = prev.(DerivedType).getBaseType()
+            )
+        }
+
+        string kindOf(UserType d) {
+            if d instanceof Union then result = "union"
+            else if d instanceof Struct then result = "struct"
+            else if d instanceof Class then result = "class"
+            else result = "enum"
+        }
+
+        from string section, string owner, string name, string type, string filename, int startline, int endline, int offset, int size
+        where
+        exists(TypedefType t, int step | t = chain(step) |
+            section = "typedef" and owner = step.toString() and name = t.getName() and
+            type = t.getBaseType().toString() and filename = relativePath(t.getLocation()) and
+            startline = t.getLocation().getStartLine() and endline = t.getLocation().getEndLine() and
+            offset = -1 and size = sizeOf(t)
+        )
+        or
+        exists(UserType d, Location l |
+            d = chain(_) and (d instanceof Class or d instanceof Enum) and l = d.getDefinitionLocation()
+        |
+            section = "definition" and owner = "" and name = d.getQualifiedName() and
+            type = kindOf(d) and filename = relativePath(l) and
+            startline = l.getStartLine() and endline = l.getEndLine() and
+            offset = -1 and size = sizeOf(d)
+        )
+        or
+        exists(Class d, Field f | d = chain(_) and f.getDeclaringType() = d |
+            section = "member" and owner = d.getQualifiedName() and name = f.getName() and
+            (
+                if f instanceof BitField
+                then type = f.getType().toString() + " : " + f.(BitField).getNumBits().toString()
+                else type = f.getType().toString()
+            ) and
+            filename = relativePath(f.getLocation()) and
+            startline = f.getLocation().getStartLine() and endline = f.getLocation().getEndLine() and
+            (if exists(f.getByteOffset()) then offset = f.getByteOffset() else offset = -1) and
+            size = sizeOf(f.getType())
+        )
+        or
+        exists(Class d, ClassDerivation der | d = chain(_) and der.getDerivedClass() = d |
+            section = "base" and owner = d.getQualifiedName() and
+            name = der.getBaseClass().getQualifiedName() and
+            (if der.isVirtual() then type = "virtual" else type = "") and
+            filename = relativePath(der.getBaseClass().getLocation()) and
+            startline = der.getBaseClass().getLocation().getStartLine() and
+            endline = der.getBaseClass().getLocation().getEndLine() and
+            offset = -1 and size = sizeOf(der.getBaseClass())
+        )
+        select section, owner, name, type, filename, startline, endline, offset, size
+        "#, "m"),
+        Language::Java => (r#"
+        import java
+
+        string relativePath(Location l) {
+            if exists(l.getFile().getRelativePath())
+            then result = l.getFile().getRelativePath()
+            else result = ""
+        }
+
+        predicate mentioned(RefType t) {
+            exists(TypeAccess a |
+                a.getFile().getRelativePath() = {{filename}} and
+                a.getLocation().getStartLine() = {{line}} and
+                {{column}}
+                t = a.getType().(RefType).getSourceDeclaration() and
+                t.getName() = {{typename}}
+            )
+        }
+
+        RefType start() {
+            mentioned(result)
+            or
+            not mentioned(_) and result.fromSource() and result.getName() = {{typename}}
+        }
+
+        string kindOf(RefType d) {
+            if d instanceof Interface then result = "interface"
+            else if d instanceof EnumType then result = "enum"
+            else result = "class"
+        }
+
+        from string section, string owner, string name, string type, string filename, int startline, int endline, int offset, int size
+        where
+        exists(RefType d | d = start() |
+            section = "definition" and owner = "" and name = d.getQualifiedName() and
+            type = kindOf(d) and filename = relativePath(d.getLocation()) and
+            startline = d.getLocation().getStartLine() and endline = d.getLocation().getEndLine() and
+            offset = -1 and size = -1
+        )
+        or
+        exists(RefType d, Field f | d = start() and f.getDeclaringType() = d |
+            section = "member" and owner = d.getQualifiedName() and name = f.getName() and
+            type = f.getType().toString() and filename = relativePath(f.getLocation()) and
+            startline = f.getLocation().getStartLine() and endline = f.getLocation().getEndLine() and
+            offset = -1 and size = -1
+        )
+        or
+        exists(RefType d, RefType base | d = start() and base = d.getASupertype().getSourceDeclaration() |
+            not base instanceof TypeObject and
+            section = "base" and owner = d.getQualifiedName() and name = base.getQualifiedName() and
+            type = "" and filename = relativePath(base.getLocation()) and
+            startline = base.getLocation().getStartLine() and endline = base.getLocation().getEndLine() and
+            offset = -1 and size = -1
+        )
+        select section, owner, name, type, filename, startline, endline, offset, size
+        "#, "a"),
+        _ => return Err(unsupported(language, "type definitions")),
+    };
+    QlTemplate::new(query)
+        .string("filename", filename)
+        .int("line", line)
+        .raw("column", column_filter(mention, line, column)?)
+        .ident("typename", typename)
+        .build()
+}
+
+/// 이름이 정규식과 맞는 소스코드 안의 심볼. kinds가 비어 있으면 모든 종류
+/// 컬럼: kind(function/global/type/macro), name, filename, line
+pub fn symbols(language: Language, regex: &str, kinds: &[SymbolKind]) -> Result<String> {
+    let query = match language {
+        Language::Cpp => r#"
+        import cpp
+
+        from string kind, string name, Location l
+        where
+        ({{kinds}}) and
+        (
+            exists(Function f | f.getName().regexpMatch({{regex}}) |
+                kind = "function" and name = f.getQualifiedName() and l = f.getLocation()
+            )
+            or
+            exists(GlobalVariable v | v.getName().regexpMatch({{regex}}) |
+                kind = "global" and name = v.getQualifiedName() and l = v.getLocation()
+            )
+            or
+            exists(UserType t | t.getName().regexpMatch({{regex}}) |
+                kind = "type" and name = t.getQualifiedName() and l = t.getLocation()
+            )
+            or
+            exists(Macro m | m.getName().regexpMatch({{regex}}) |
+                kind = "macro" and name = m.getName() and l = m.getLocation()
+            )
+        ) and
+        exists(l.getFile().getRelativePath())
+        select
+        kind,
+        name,
+        l.getFile().getRelativePath() as filename,
+        l.getStartLine() as line
+        "#,
+        Language::Java => r#"
+        import java
+
+        from string kind, string name, Location l
+        where
+        ({{kinds}}) and
+        (
+            exists(Callable f | f.fromSource() and f.getName().regexpMatch({{regex}}) |
+                kind = "function" and name = f.getQualifiedName() and l = f.getLocation()
+            )
+            or
+            exists(Field v | v.fromSource() and v.isStatic() and v.getName().regexpMatch({{regex}}) |
+                kind = "global" and name = v.getDeclaringType().getQualifiedName() + "." + v.getName() and l = v.getLocation()
+            )
+            or
+            exists(RefType t | t.fromSource() and t.getName().regexpMatch({{regex}}) |
+                kind = "type" and name = t.getQualifiedName() and l = t.getLocation()
+            )
+        ) and
+        exists(l.getFile().getRelativePath())
+        select
+        kind,
+        name,
+        l.getFile().getRelativePath() as filename,
+        l.getStartLine() as line
+        "#,
+        _ => return Err(unsupported(language, "symbol search")),
+    };
+    // 매크로는 C/C++에만 있음
+    let has_macros = language == Language::Cpp;
+    if !has_macros && kinds.contains(&SymbolKind::Macro) {
+        return Err(AppError::InvalidArgument(format!("{} has no macro symbols", language)));
+    }
+    let kinds: Vec<SymbolKind> = if kinds.is_empty() {
+        SymbolKind::ALL.iter().copied().filter(|kind| has_macros || *kind != SymbolKind::Macro).collect()
+    } else {
+        kinds.to_vec()
+    };
+    let kinds: Vec<String> = kinds.iter().map(|kind| format!("kind = \"{}\"", kind.as_str())).collect();
+    QlTemplate::new(query)
+        .raw("kinds", kinds.join(" or "))
+        .string("regex", regex)
+        .build()
+}
+
+/// 함수에서 depth 단계까지 닿는 호출 간선
+/// 컬럼: caller, caller_file, caller_line, callee, callee_file, callee_line, kind(direct/virtual/pointer), call_line, depth
+pub fn call_graph(language: Language, function: &str, direction: CallDirection, depth: u32) -> Result<String> {
+    if depth == 0 || depth > MAX_DEPTH {
+        return Err(AppError::InvalidArgument(format!("depth must be between 1 and {}", MAX_DEPTH)));
+    }
+    let query = match language {
+        // 함수 포인터 호출은 함수 주소가 지역적으로 흘러 들어가거나
+        // 같은 구조체 필드에 저장된 경우(핸들러 테이블 등)만 해석
+        Language::Cpp => r#"
+        import cpp
+        import semmle.code.cpp.dataflow.new.DataFlow
+
+        string relativePath(Function f) {
+            if exists(f.getFile().getRelativePath())
+            then result = f.getFile().getRelativePath()
+            else result = ""
+        }
+
+        predicate pointerTarget(ExprCall c, Function callee) {
+            exists(FunctionAccess fa | fa.getTarget() = callee |
+                DataFlow::localExprFlow(fa, c.getExpr())
+                or
+                exists(Field f |
+                    c.getExpr() = f.getAnAccess() and
+                    (fa = f.getAnAssignedValue() or fa = any(ClassAggregateLiteral l).getAFieldExpr(f))
+                )
+            )
+        }
+
+        predicate callEdge(Function caller, Function callee, Call c, string kind) {
+            c.getEnclosingFunction() = caller and
+            (
+                c.getTarget() = callee and not c.(FunctionCall).isVirtual() and kind = "direct"
+                or
+                c.(FunctionCall).isVirtual() and kind = "virtual" and
+                (callee = c.getTarget() or callee.(MemberFunction).overrides+(c.getTarget()))
+                or
+                pointerTarget(c, callee) and kind = "pointer"
+            )
+        }
+
+        predicate reached(Function f, int depth) {
+            f.getName() = {{function}} and depth = 0
+            or
+            exists(Function prev | reached(prev, depth - 1) and depth <= {{depth}} and {{step}})
+        }
+
+        from Function caller, Function callee, Call c, string kind, int depth
+        where
+        callEdge(caller, callee, c, kind) and
+        reached({{expanding}}, depth) and
+        depth < {{depth}}
+        select
+        caller.getQualifiedName() as caller,
+        relativePath(caller) as caller_file,
+        caller.getLocation().getStartLine() as caller_line,
+        callee.getQualifiedName() as callee,
+        relativePath(callee) as callee_file,
+        callee.getLocation().getStartLine() as callee_line,
+        kind,
+        c.getLocation().getStartLine() as call_line,
+        depth
+        "#,
+        Language::Java => r#"
+        import java
+        import semmle.code.java.dispatch.VirtualDispatch
+
+        string relativePath(Callable f) {
+            if exists(f.getFile().getRelativePath())
+            then result = f.getFile().getRelativePath()
+            else result = ""
+        }
+
+        predicate callEdge(Callable caller, Callable callee, Call c, string kind) {
+            c.getEnclosingCallable() = caller and
+            (
+                callee = c.getCallee().getSourceDeclaration() and kind = "direct"
+                or
+                callee = viableImpl(c) and callee != c.getCallee().getSourceDeclaration() and kind = "virtual"
+            )
+        }
+
+        predicate reached(Callable f, int depth) {
+            f.getName() = {{function}} and depth = 0
+            or
+            exists(Callable prev | reached(prev, depth - 1) and depth <= {{depth}} and {{step}})
+        }
+
+        from Callable caller, Callable callee, Call c, string kind, int depth
+        where
+        callEdge(caller, callee, c, kind) and
+        reached({{expanding}}, depth) and
+        depth < {{depth}}
+        select
+        caller.getQualifiedName() as caller,
+        relativePath(caller) as caller_file,
+        caller.getLocation().getStartLine() as caller_line,
+        callee.getQualifiedName() as callee,
+        relativePath(callee) as callee_file,
+        callee.getLocation().getStartLine() as callee_line,
+        kind,
+        c.getLocation().getStartLine() as call_line,
+        depth
+        "#,
+        _ => return Err(unsupported(language, "call graphs")),
+    };
+    let (step, expanding) = match direction {
+        CallDirection::Callees => ("callEdge(prev, f, _, _)", "caller"),
+        CallDirection::Callers => ("callEdge(f, prev, _, _)", "callee"),
+    };
+    QlTemplate::new(query)
+        .ident("function", function)
+        .int("depth", depth)
+        .raw("step", step)
+        .raw("expanding", expanding)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Language; 5] = [
+        Language::Cpp,
+        Language::Java,
+        Language::Python,
+        Language::JavaScript,
+        Language::CSharp,
+    ];
+
+    #[test]
+    fn test_templates_import_database_language() {
+        for language in ALL {
+            let import = format!("import {}\n", language);
+            assert!(var_definitions(language, "a", 1, None, "x").unwrap().contains(&import));
+            assert!(var_refs(language, "a", 1, None, "x").unwrap().contains(&import));
+            assert!(function_implementation(language, "a", 1, None, "f").unwrap().contains(&import));
+            assert!(function_refs(language, Some("a"), "f").unwrap().contains(&import));
+            assert!(function_code(language, None, "f").unwrap().contains(&import));
+        }
+    }
+
+    #[test]
+    fn test_var_refs_access_kinds() {
+        let query = var_refs(Language::Cpp, "lib/url.c", 10, None, "len").unwrap();
+        assert!(query.contains(r#"(ref.isModified() and kind = "write")"#));
+        assert!(query.contains(r#"(ref.getParent() instanceof AddressOfExpr and kind = "address")"#));
+        // 복합 대입과 증감 연산은 write이면서 read
+        assert!(query.contains("ref = any(AssignOperation op).getLValue() or"));
+        assert!(query.contains("ref = any(CrementOperation op).getOperand()"));
+
+        let query = var_refs(Language::Java, "A.java", 10, None, "len").unwrap();
+        assert!(query.contains(r#"(ref.isVarWrite() and kind = "write")"#));
+        assert!(query.contains(r#"(ref.isVarRead() and kind = "read")"#));
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(matches!(
+            var_definitions(Language::Go, "main.go", 1, None, "x"),
+            Err(AppError::UnsupportedLanguage(_))
+        ));
+    }
+
+    #[test]
+    fn test_overrides_unsupported_language() {
+        assert!(function_overrides(Language::Java, "A.f").is_ok());
+        assert!(matches!(
+            function_overrides(Language::Python, "f"),
+            Err(AppError::UnsupportedLanguage(_))
+        ));
+    }
+
+    #[test]
+    fn test_optional_file_filter() {
+        let scoped = function_refs(Language::Cpp, Some("lib/url.c"), "parse_url").unwrap();
+        assert!(scoped.contains(r#"target.getFile().getRelativePath() = "lib/url.c" and"#));
+        let unscoped = function_refs(Language::Cpp, None, "parse_url").unwrap();
+        assert!(!unscoped.contains("target.getFile()"));
+    }
+
+    #[test]
+    fn test_hostile_arguments() {
+        // 파일명은 이스케이프되어 문자열 안에 머묾
+        let query = var_definitions(Language::Cpp, "a.c\" or any() or \"", 1, None, "x").unwrap();
+        assert!(query.contains(r#"= "a.c\" or any() or \"" and"#));
+
+        // 식별자 자리에 QL 코드를 넣으면 거부
+        for language in ALL {
+            assert!(matches!(
+                var_refs(language, "a.c", 1, None, "x\") or exists(Function f | "),
+                Err(AppError::InvalidArgument(_))
+            ));
+            assert!(matches!(
+                function_code(language, Some("a.c"), "f\nselect 1"),
+                Err(AppError::InvalidArgument(_))
+            ));
+        }
+
+        // matches() 패턴의 `_`는 문자 그대로
+        let query = function_implementation(Language::Cpp, "a.c", 1, None, "parse_url").unwrap();
+        assert!(query.contains(r#"matches("%parse\\_url%")"#));
+    }
+
+    #[test]
+    fn test_dataflow_query() {
+        let source = FlowSpec::Argument { function: "recv".to_string(), index: 1 };
+        let sink = FlowSpec::Argument { function: "memcpy".to_string(), index: 2 };
+        let query = dataflow(Language::Cpp, &source, &sink).unwrap();
+        assert!(query.contains("@kind path-problem"));
+        assert!(query.contains("TaintTracking::Global<AgentFlowConfig>"));
+        assert!(query.contains(r#"c.getTarget().getName() = "recv" and n.asDefiningArgument() = c.getArgument(1)"#));
+        assert!(query.contains(r#"c.getTarget().getName() = "memcpy" and (n.asExpr() = c.getArgument(2)"#));
+        assert_eq!(Language::from_query(&query), Some(Language::Cpp));
+
+        let spec: FlowSpec = serde_json::from_str(r#"{"kind": "parameter", "function": "parse_url", "index": 0}"#).unwrap();
+        assert_eq!(spec, FlowSpec::Parameter { function: "parse_url".to_string(), index: 0 });
+
+        let hostile = FlowSpec::ReturnValue { function: "getenv\") or any() or (\"".to_string() };
+        assert!(matches!(dataflow(Language::Cpp, &hostile, &sink), Err(AppError::InvalidArgument(_))));
+        assert!(matches!(dataflow(Language::Python, &source, &sink), Err(AppError::UnsupportedLanguage(_))));
+    }
+
+    #[test]
+    fn test_call_graph_query() {
+        let callees = call_graph(Language::Cpp, "main", CallDirection::Callees, 3).unwrap();
+        assert!(callees.contains(r#"f.getName() = "main" and depth = 0"#));
+        assert!(callees.contains("depth <= 3 and callEdge(prev, f, _, _)"));
+        assert!(callees.contains("reached(caller, depth)"));
+
+        let callers = call_graph(Language::Java, "handle", CallDirection::Callers, 2).unwrap();
+        assert!(callers.contains("callEdge(f, prev, _, _)"));
+        assert!(callers.contains("reached(callee, depth)"));
+
+        assert!(matches!(call_graph(Language::Cpp, "main", CallDirection::Callees, 0), Err(AppError::InvalidArgument(_))));
+        assert!(matches!(call_graph(Language::Cpp, "main", CallDirection::Callees, MAX_DEPTH + 1), Err(AppError::InvalidArgument(_))));
+        assert!(matches!(call_graph(Language::Go, "main", CallDirection::Callees, 1), Err(AppError::UnsupportedLanguage(_))));
+    }
+
+    #[test]
+    fn test_type_definition_query() {
+        let query = type_definition(Language::Cpp, "lib/urlapi.c", 120, None, "CURLU").unwrap();
+        assert!(query.contains(r#"t.getName() = "CURLU""#));
+        assert!(query.contains("result = prev.(TypedefType).getBaseType()"));
+        assert!(query.contains("f.getByteOffset()"));
+        assert!(query.contains("not exists(UserType t | candidate(t) and t.hasDefinition())"));
+        assert!(!query.contains("{{"));
+
+        assert!(matches!(type_definition(Language::Cpp, "a.c", 1, None, "struct foo"), Err(AppError::InvalidArgument(_))));
+        assert!(matches!(type_definition(Language::Python, "a.py", 1, None, "Foo"), Err(AppError::UnsupportedLanguage(_))));
+    }
+
+    #[test]
+    fn test_macro_invocation_query() {
+        let query = macro_invocation(Language::Cpp, "lib/url.c", 42, None, "CURLMIN").unwrap();
+        assert!(query.contains(r#"m.getName() = "CURLMIN""#));
+        assert!(query.contains("mi.getLocation().getStartLine() = 42"));
+        assert!(query.contains("then expanded = mi.getExpandedArgument(index)"));
+        assert!(query.contains("expression = mi.getExpr().toString()"));
+        assert!(matches!(macro_invocation(Language::Java, "A.java", 1, None, "x"), Err(AppError::UnsupportedLanguage(_))));
+    }
+
+    #[test]
+    fn test_symbols_query() {
+        let query = symbols(Language::Cpp, "(?i).*parse_url.*", &[SymbolKind::Function, SymbolKind::Macro]).unwrap();
+        assert!(query.contains(r#"(kind = "function" or kind = "macro") and"#));
+        assert!(query.contains(r#"f.getName().regexpMatch("(?i).*parse_url.*")"#));
+
+        // 정규식은 문자열 리터럴로만 들어감
+        let query = symbols(Language::Java, "a\") or any() or (\"", &[]).unwrap();
+        assert!(query.contains(r#"regexpMatch("a\") or any() or (\"")"#));
+        assert!(query.contains(r#"(kind = "function" or kind = "global" or kind = "type") and"#));
+        assert!(matches!(symbols(Language::Java, ".*", &[SymbolKind::Macro]), Err(AppError::InvalidArgument(_))));
+        assert!(matches!(symbols(Language::Ruby, ".*", &[]), Err(AppError::UnsupportedLanguage(_))));
+    }
+
+    #[test]
+    fn test_column_filter() {
+        let query = var_definitions(Language::Cpp, "lib/url.c", 10, Some(17), "len").unwrap();
+        assert!(query.contains(
+            "a.getLocation().getStartColumn() <= 17 and (a.getLocation().getEndLine() > 10 or a.getLocation().getEndColumn() >= 17) and"
+        ));
+        assert!(query.contains("kindOf(v) as kind"));
+        assert!(query.contains("as shadows"));
+
+        // column이 없으면 조건도 없음
+        for language in [Language::Cpp, Language::Java] {
+            let query = function_implementation(language, "a", 1, None, "f").unwrap();
+            assert!(!query.contains("getStartColumn() <="));
+        }
+        let query = type_definition(Language::Java, "A.java", 3, Some(5), "Foo").unwrap();
+        assert!(query.contains("a.getLocation().getStartColumn() <= 5"));
+        let query = macro_invocation(Language::Cpp, "a.c", 3, Some(5), "MIN").unwrap();
+        assert!(query.contains("mi.getLocation().getStartColumn() <= 5"));
+    }
+
+    #[test]
+    fn test_function_summary_query() {
+        let query = function_summary(Language::Cpp, Some("src/tool_operate.c"), "operate").unwrap();
+        assert!(query.contains(r#"f.getDefinitionLocation().getFile().getRelativePath() = "src/tool_operate.c" and"#));
+        assert!(query.contains(r#"section = "allocation""#));
+        let query = function_summary(Language::Java, None, "handle").unwrap();
+        assert!(query.contains("LoopStmt"));
+        assert!(matches!(function_summary(Language::JavaScript, None, "f"), Err(AppError::UnsupportedLanguage(_))));
+    }
+}
@@ -0,0 +1,347 @@
+// query_server.rs - `codeql execute query-server2` 클라이언트
+//
+// 쿼리마다 `codeql query run`을 띄우면 JVM 시작, 데이터베이스 로딩, 라이브러리 컴파일을
+// 매번 다시 합니다. query-server2를 한 번 띄워 두고 JSON-RPC(stdio, Content-Length 헤더)로
+// 쿼리를 보내면 이 비용을 세션 전체에서 한 번만 냅니다.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use crate::error::{AppError, Result};
+use crate::tools::codeql::{stderr_tail, STDERR_TAIL_LINES};
+
+// evaluation/runQuery 결과의 resultType
+const RESULT_SUCCESS: i64 = 0;
+
+/// JSON-RPC 메시지를 `Content-Length` 헤더를 붙여 인코딩
+pub fn encode_message(message: &Value) -> Vec<u8> {
+    let body = message.to_string();
+    let mut frame = format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes();
+    frame.extend_from_slice(body.as_bytes());
+    frame
+}
+
+/// 메시지 하나를 읽음. 스트림이 헤더 전에 끝나면 None
+pub async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            if content_length.is_none() {
+                return Ok(None);
+            }
+            return Err(protocol_error("Stream ended inside message header"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            // 메시지 사이의 빈 줄은 무시
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse::<usize>()
+                    .map_err(|_| protocol_error(&format!("Invalid Content-Length: {}", value.trim())))?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+// 서버 stderr를 log에 이어 붙임. 오래 떠 있는 서버라 끝부분만 남김
+async fn collect_stderr<R: tokio::io::AsyncRead + Unpin>(stderr: R, log: Arc<Mutex<String>>) {
+    const MAX_LOG_BYTES: usize = 64 * 1024;
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let mut log = log.lock().unwrap();
+        log.push_str(&line);
+        log.push('\n');
+        if log.len() > MAX_LOG_BYTES {
+            let cut = log.len() - MAX_LOG_BYTES / 2;
+            let cut = (cut..log.len()).find(|&i| log.is_char_boundary(i)).unwrap_or(log.len());
+            log.drain(..cut);
+        }
+    }
+}
+
+fn protocol_error(message: &str) -> AppError {
+    AppError::CodeQLError(format!("query server protocol error: {}", message))
+}
+
+/// 요청을 보내고 같은 id의 응답을 기다리는 JSON-RPC 연결
+pub struct Connection<R, W> {
+    reader: R,
+    writer: W,
+    next_id: u64,
+}
+
+impl<R: AsyncBufRead + Unpin, W: AsyncWrite + Unpin> Connection<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Connection {
+            reader,
+            writer,
+            next_id: 1,
+        }
+    }
+
+    /// 응답이 오기 전의 알림(진행 상황 등)은 건너뜀
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        self.writer.write_all(&encode_message(&request)).await?;
+        self.writer.flush().await?;
+
+        loop {
+            let message = read_message(&mut self.reader)
+                .await?
+                .ok_or_else(|| protocol_error("Query server closed the connection"))?;
+            if message.get("method").is_some() || message.get("id") != Some(&json!(id)) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(AppError::CodeQLError(format!(
+                    "{} failed: {}",
+                    method,
+                    error.get("message").and_then(Value::as_str).unwrap_or("unknown error")
+                )));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+}
+
+/// query-server2 요청 파라미터. 실제 인자는 body에 넣고 진행 상황 알림에 쓸 progressId를 붙임
+pub fn with_progress_id(body: Value, progress_id: u64) -> Value {
+    json!({"body": body, "progressId": progress_id})
+}
+
+/// evaluation/runQuery의 body
+pub fn run_query_body(db_path: &Path, query_path: &Path, output_path: &Path, additional_packs: &[&Path]) -> Value {
+    json!({
+        "db": db_path,
+        "queryPath": query_path,
+        "outputPath": output_path,
+        "additionalPacks": additional_packs,
+        "externalInputs": {},
+        "singletonExternalInputs": {},
+        "target": {"query": {}}
+    })
+}
+
+/// 실행 중인 query-server2 프로세스. 드롭되면 프로세스도 종료
+pub struct QueryServer {
+    // kill_on_drop을 위해 들고 있음
+    _child: Child,
+    connection: Connection<BufReader<ChildStdout>, ChildStdin>,
+    next_progress_id: u64,
+}
+
+impl QueryServer {
+    /// 서버를 띄우고 데이터베이스를 등록
+    ///
+    /// timeout 안에 등록까지 끝나지 않으면 서버를 종료하고 Timeout을 반환
+    pub async fn start(db_path: &Path, timeout: Option<Duration>) -> Result<Self> {
+        let stderr_log = Arc::new(Mutex::new(String::new()));
+        let (stderr_done, stderr_closed) = tokio::sync::oneshot::channel();
+        let starting = Self::spawn_and_register(db_path, stderr_log.clone(), stderr_done);
+        let started = match timeout {
+            Some(limit) => tokio::time::timeout(limit, starting).await.unwrap_or(Err(AppError::Timeout {
+                operation: "query-server2 start".to_string(),
+                timeout: limit,
+            })),
+            None => starting.await,
+        };
+        let Err(e) = started else {
+            return started;
+        };
+
+        // 실패 원인은 보통 서버가 stderr에 남긴 로그에 있음. 서버는 이미 종료됐으니 남은 출력을 마저 읽음
+        let _ = tokio::time::timeout(Duration::from_secs(1), stderr_closed).await;
+        let stderr = stderr_tail(&stderr_log.lock().unwrap(), STDERR_TAIL_LINES);
+        if stderr.is_empty() {
+            Err(e)
+        } else {
+            Err(AppError::CodeQLError(format!("{}\n{}", e, stderr)))
+        }
+    }
+
+    async fn spawn_and_register(
+        db_path: &Path,
+        stderr_log: Arc<Mutex<String>>,
+        stderr_done: tokio::sync::oneshot::Sender<()>,
+    ) -> Result<Self> {
+        let mut child = tokio::process::Command::new("codeql")
+            .arg("execute")
+            .arg("query-server2")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let stdin = child.stdin.take()
+            .ok_or_else(|| protocol_error("Query server has no stdin"))?;
+        let stdout = child.stdout.take()
+            .ok_or_else(|| protocol_error("Query server has no stdout"))?;
+        // stderr를 읽지 않으면 파이프가 차서 서버가 멈추므로 계속 비워 줌
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(async move {
+                collect_stderr(stderr, stderr_log).await;
+                let _ = stderr_done.send(());
+            });
+        }
+
+        let mut server = QueryServer {
+            _child: child,
+            connection: Connection::new(BufReader::new(stdout), stdin),
+            next_progress_id: 1,
+        };
+        server.request("evaluation/registerDatabases", json!({"databases": [db_path]})).await?;
+        Ok(server)
+    }
+
+    async fn request(&mut self, method: &str, body: Value) -> Result<Value> {
+        let progress_id = self.next_progress_id;
+        self.next_progress_id += 1;
+        self.connection.request(method, with_progress_id(body, progress_id)).await
+    }
+
+    /// 쿼리를 컴파일, 실행해서 결과를 output_path(bqrs)에 씀
+    ///
+    /// 바깥 에러는 서버와의 통신 실패, 쿼리 자체의 실패는 RunQueryResult로 구분
+    pub async fn run_query(
+        &mut self,
+        db_path: &Path,
+        query_path: &Path,
+        output_path: &Path,
+        additional_packs: &[&Path],
+    ) -> Result<RunQueryResult> {
+        let body = run_query_body(db_path, query_path, output_path, additional_packs);
+        let result = self.request("evaluation/runQuery", body).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
+
+/// evaluation/runQuery 응답
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunQueryResult {
+    pub result_type: i64,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl RunQueryResult {
+    pub fn into_result(self) -> Result<()> {
+        if self.result_type == RESULT_SUCCESS {
+            return Ok(());
+        }
+        Err(AppError::CodeQLError(
+            self.message
+                .unwrap_or_else(|| format!("Query evaluation failed (resultType {})", self.result_type)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_framing_roundtrip() {
+        let first = json!({"jsonrpc": "2.0", "id": 1, "result": {"resultType": 0}});
+        let second = json!({"jsonrpc": "2.0", "method": "ql/progressUpdated", "params": {"message": "한글"}});
+        let mut stream = encode_message(&first);
+        stream.extend(encode_message(&second));
+
+        let mut reader: &[u8] = &stream;
+        assert_eq!(read_message(&mut reader).await.unwrap(), Some(first));
+        assert_eq!(read_message(&mut reader).await.unwrap(), Some(second));
+        assert_eq!(read_message(&mut reader).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_message() {
+        let mut reader: &[u8] = b"Content-Length: 20\r\n\r\n{\"id\":";
+        assert!(read_message(&mut reader).await.is_err());
+        let mut reader: &[u8] = b"Content-Length: abc\r\n\r\n";
+        assert!(read_message(&mut reader).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_collect_stderr() {
+        let log = Arc::new(Mutex::new(String::new()));
+        let stderr: &[u8] = b"Starting query server\nA fatal error occurred: no such database\n";
+        collect_stderr(stderr, log.clone()).await;
+        let log = log.lock().unwrap();
+        assert_eq!(stderr_tail(&log, 1), "A fatal error occurred: no such database");
+    }
+
+    #[tokio::test]
+    async fn test_request_skips_notifications() {
+        let (client, server) = tokio::io::duplex(4096);
+        let (client_read, client_write) = tokio::io::split(client);
+        let (server_read, mut server_write) = tokio::io::split(server);
+
+        let fake_server = tokio::spawn(async move {
+            let mut reader = BufReader::new(server_read);
+            let request = read_message(&mut reader).await.unwrap().unwrap();
+            assert_eq!(request["method"], "evaluation/runQuery");
+            // 인자는 body 안에, progressId는 바깥에
+            assert_eq!(request["params"]["progressId"], 7);
+            assert_eq!(request["params"]["body"]["queryPath"], "/tmp/q/query.ql");
+            assert_eq!(request["params"]["body"]["target"], json!({"query": {}}));
+            assert!(request["params"].get("queryPath").is_none());
+            let id = request["id"].clone();
+            let progress = json!({"jsonrpc": "2.0", "method": "ql/progressUpdated", "params": {"step": 1}});
+            server_write.write_all(&encode_message(&progress)).await.unwrap();
+            let response = json!({"jsonrpc": "2.0", "id": id, "result": {"resultType": 1, "message": "compilation failed"}});
+            server_write.write_all(&encode_message(&response)).await.unwrap();
+        });
+
+        let mut connection = Connection::new(BufReader::new(client_read), client_write);
+        let body = run_query_body(Path::new("/tmp/db"), Path::new("/tmp/q/query.ql"), Path::new("/tmp/q/out.bqrs"), &[]);
+        let result = connection.request("evaluation/runQuery", with_progress_id(body, 7)).await.unwrap();
+        fake_server.await.unwrap();
+
+        let result: RunQueryResult = serde_json::from_value(result).unwrap();
+        assert!(matches!(result.into_result(), Err(AppError::CodeQLError(m)) if m == "compilation failed"));
+        let success: RunQueryResult = serde_json::from_value(json!({"resultType": 0, "evaluationTime": 12})).unwrap();
+        assert!(success.into_result().is_ok());
+    }
+
+    // CODEQL_TEST_DB에 데이터베이스 경로를 주고 `cargo test -- --ignored`로 실행
+    #[tokio::test]
+    #[ignore = "CodeQL CLI와 CODEQL_TEST_DB 데이터베이스가 필요합니다"]
+    async fn test_real_query_server() {
+        use crate::tools::language::Language;
+        use crate::tools::pack::{PackQuery, QueryPacks};
+        use crate::tools::workspace::test_dir;
+
+        let db_path = std::path::absolute(std::env::var("CODEQL_TEST_DB").expect("CODEQL_TEST_DB")).unwrap();
+        let language = Language::from_database(&db_path).unwrap();
+        let scratch = test_dir("query_server");
+        let pack_dir = QueryPacks::new(scratch.join("packs")).ensure(language).await.unwrap();
+        let query = PackQuery::write(&pack_dir, "query_server", &format!("import {}\nselect 1 as one", language)).unwrap();
+        let output = scratch.join("out.bqrs");
+
+        let mut server = QueryServer::start(&db_path, Some(Duration::from_secs(600))).await.unwrap();
+        let result = server.run_query(&db_path, query.path(), &output, &[]).await.unwrap();
+        result.into_result().unwrap();
+        assert!(output.exists());
+    }
+}
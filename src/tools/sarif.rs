@@ -0,0 +1,316 @@
+// sarif.rs - `codeql database analyze` 등이 만드는 SARIF 결과 파서
+//
+// SARIF 전체 스펙 중 분석 결과를 검토하는 데 필요한 부분(결과, 위치, 코드 흐름, 규칙)만 읽습니다.
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::Result;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SarifLog {
+    #[serde(default)]
+    pub runs: Vec<Run>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Run {
+    pub tool: Tool,
+    #[serde(default)]
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tool {
+    pub driver: Driver,
+    // 쿼리 팩의 규칙은 extensions 쪽에 들어 있음
+    #[serde(default)]
+    pub extensions: Vec<Driver>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Driver {
+    pub name: String,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub short_description: Option<Message>,
+    #[serde(default)]
+    pub full_description: Option<Message>,
+    #[serde(default)]
+    pub properties: RuleProperties,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RuleProperties {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub precision: Option<String>,
+    #[serde(default, rename = "problem.severity")]
+    pub problem_severity: Option<String>,
+    // "8.8"처럼 문자열로 옴
+    #[serde(default, rename = "security-severity")]
+    pub security_severity: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Message {
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    #[serde(default)]
+    pub rule_index: Option<usize>,
+    #[serde(default)]
+    pub rule: Option<RuleReference>,
+    #[serde(default)]
+    pub level: Option<String>,
+    pub message: Message,
+    #[serde(default)]
+    pub locations: Vec<SarifLocation>,
+    #[serde(default)]
+    pub code_flows: Vec<CodeFlow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleReference {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub index: Option<usize>,
+    #[serde(default)]
+    pub tool_component: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    #[serde(default)]
+    pub physical_location: Option<PhysicalLocation>,
+    #[serde(default)]
+    pub message: Option<Message>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    #[serde(default)]
+    pub region: Option<Region>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    #[serde(default)]
+    pub start_line: u32,
+    #[serde(default)]
+    pub start_column: Option<u32>,
+    // 생략되면 start_line과 같은 줄
+    #[serde(default)]
+    pub end_line: Option<u32>,
+    #[serde(default)]
+    pub end_column: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeFlow {
+    #[serde(default)]
+    pub thread_flows: Vec<ThreadFlow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThreadFlow {
+    #[serde(default)]
+    pub locations: Vec<ThreadFlowLocation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThreadFlowLocation {
+    pub location: SarifLocation,
+}
+
+/// 모델이 하나씩 검토할 수 있게 정리한 결과
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_severity: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<FindingLocation>,
+    /// 코드 흐름마다 source에서 sink까지 순서대로 나열한 위치
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub flows: Vec<Vec<FindingLocation>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FindingLocation {
+    pub filename: String,
+    pub startline: u32,
+    pub endline: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl SarifLocation {
+    pub fn to_finding_location(&self) -> Option<FindingLocation> {
+        let physical = self.physical_location.as_ref()?;
+        let region = physical.region.clone().unwrap_or_default();
+        Some(FindingLocation {
+            filename: physical.artifact_location.uri.trim_start_matches("file://").to_string(),
+            startline: region.start_line,
+            endline: region.end_line.unwrap_or(region.start_line),
+            column: region.start_column,
+            message: self.message.as_ref().map(|m| m.text.clone()).filter(|text| !text.is_empty()),
+        })
+    }
+}
+
+impl SarifLog {
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// 모든 run의 결과를 규칙 정보와 합쳐서 반환
+    pub fn findings(&self) -> Vec<Finding> {
+        self.runs.iter().flat_map(Run::findings).collect()
+    }
+}
+
+impl Run {
+    // 결과의 규칙은 driver 또는 extensions 중 하나에 있음
+    fn find_rule(&self, result: &SarifResult) -> Option<&Rule> {
+        let id = result.rule_id.as_deref().or(result.rule.as_ref().and_then(|r| r.id.as_deref()));
+        let drivers = || std::iter::once(&self.tool.driver).chain(&self.tool.extensions);
+        if let Some(id) = id {
+            if let Some(rule) = drivers().flat_map(|driver| &driver.rules).find(|rule| rule.id == id) {
+                return Some(rule);
+            }
+        }
+        result.rule_index.and_then(|index| self.tool.driver.rules.get(index))
+    }
+
+    pub fn findings(&self) -> Vec<Finding> {
+        self.results
+            .iter()
+            .map(|result| {
+                let rule = self.find_rule(result);
+                let rule_id = result.rule_id.clone()
+                    .or_else(|| rule.map(|rule| rule.id.clone()))
+                    .unwrap_or_default();
+                Finding {
+                    rule_id,
+                    rule_name: rule.and_then(|rule| rule.name.clone()),
+                    severity: result.level.clone()
+                        .or_else(|| rule.and_then(|rule| rule.properties.problem_severity.clone())),
+                    security_severity: rule.and_then(|rule| rule.properties.security_severity.clone()),
+                    message: result.message.text.clone(),
+                    location: result.locations.first().and_then(SarifLocation::to_finding_location),
+                    flows: result.code_flows
+                        .iter()
+                        .flat_map(|flow| &flow.thread_flows)
+                        .map(|thread| {
+                            thread.locations
+                                .iter()
+                                .filter_map(|step| step.location.to_finding_location())
+                                .collect()
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SARIF: &str = r#"{
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {"name": "CodeQL", "rules": []},
+                "extensions": [{
+                    "name": "codeql/cpp-queries",
+                    "rules": [{
+                        "id": "cpp/unbounded-write",
+                        "name": "cpp/unbounded-write",
+                        "shortDescription": {"text": "Unbounded write"},
+                        "properties": {"tags": ["security", "external/cwe/cwe-120"], "kind": "path-problem", "precision": "medium", "problem.severity": "error", "security-severity": "9.3"}
+                    }]
+                }]
+            },
+            "results": [{
+                "ruleId": "cpp/unbounded-write",
+                "rule": {"id": "cpp/unbounded-write", "index": 0, "toolComponent": {"index": 0}},
+                "message": {"text": "This 'call to strcpy' with input from [argv](1) may overflow the destination."},
+                "locations": [{"physicalLocation": {"artifactLocation": {"uri": "src/tool_getparam.c", "uriBaseId": "%SRCROOT%", "index": 0}, "region": {"startLine": 120, "startColumn": 5, "endColumn": 30}}}],
+                "codeFlows": [{"threadFlows": [{"locations": [
+                    {"location": {"physicalLocation": {"artifactLocation": {"uri": "src/tool_main.c"}, "region": {"startLine": 10, "startColumn": 14}}, "message": {"text": "argv"}}},
+                    {"location": {"physicalLocation": {"artifactLocation": {"uri": "src/tool_getparam.c"}, "region": {"startLine": 120, "startColumn": 20}}, "message": {"text": "nextarg"}}}
+                ]}]}]
+            }, {
+                "ruleId": "cpp/unknown",
+                "message": {"text": "no location"}
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn test_findings_with_rules_and_flows() {
+        let findings = SarifLog::parse(SARIF).unwrap().findings();
+        assert_eq!(findings.len(), 2);
+
+        let finding = &findings[0];
+        assert_eq!(finding.rule_id, "cpp/unbounded-write");
+        assert_eq!(finding.severity.as_deref(), Some("error"));
+        assert_eq!(finding.security_severity.as_deref(), Some("9.3"));
+        let location = finding.location.as_ref().unwrap();
+        assert_eq!((location.filename.as_str(), location.startline, location.endline), ("src/tool_getparam.c", 120, 120));
+
+        assert_eq!(finding.flows.len(), 1);
+        let steps = &finding.flows[0];
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].filename, "src/tool_main.c");
+        assert_eq!(steps[0].message.as_deref(), Some("argv"));
+        assert_eq!(steps[1].startline, 120);
+
+        assert_eq!(findings[1].location, None);
+        assert_eq!(findings[1].rule_name, None);
+    }
+}
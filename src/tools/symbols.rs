@@ -0,0 +1,175 @@
+// symbols.rs - 이름 일부로 심볼 검색
+//
+// 모델은 "parse_url?", "alloc"처럼 이름 일부만 아는 경우가 많습니다.
+// 쿼리는 정규식으로 후보를 모두 가져오고, 순위는 여기서 이름 유사도로 매깁니다.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Global,
+    Type,
+    Macro,
+}
+
+impl SymbolKind {
+    pub const ALL: [SymbolKind; 4] = [SymbolKind::Function, SymbolKind::Global, SymbolKind::Type, SymbolKind::Macro];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Global => "global",
+            SymbolKind::Type => "type",
+            SymbolKind::Macro => "macro",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolPattern {
+    /// `*`, `?` 와일드카드. 와일드카드가 없으면 이름의 일부로 찾음. 대소문자 무시
+    Glob(String),
+    /// 이름 전체와 맞춰 보는 정규식 (QL regexpMatch)
+    Regex(String),
+}
+
+impl SymbolPattern {
+    pub fn to_regex(&self) -> String {
+        match self {
+            SymbolPattern::Regex(regex) => regex.clone(),
+            SymbolPattern::Glob(glob) => {
+                let mut regex = String::from("(?i)");
+                if !glob.contains(['*', '?']) {
+                    regex.push_str(".*");
+                }
+                for c in glob.chars() {
+                    match c {
+                        '*' => regex.push_str(".*"),
+                        '?' => regex.push('.'),
+                        c if c.is_ascii_alphanumeric() || c == '_' => regex.push(c),
+                        c => {
+                            regex.push('\\');
+                            regex.push(c);
+                        }
+                    }
+                }
+                if !glob.contains(['*', '?']) {
+                    regex.push_str(".*");
+                }
+                regex
+            }
+        }
+    }
+
+    // 유사도 비교에 쓸 글자만 남긴 패턴
+    fn needle(&self) -> String {
+        let text = match self {
+            SymbolPattern::Glob(text) | SymbolPattern::Regex(text) => text,
+        };
+        text.chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect::<String>()
+            .to_lowercase()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub kind: String,
+    pub name: String,
+    pub filename: String,
+    pub line: u32,
+    #[serde(default)]
+    pub score: f64,
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// 0~1 사이 유사도. 같으면 1, 그다음 앞부분이 같은 이름, 포함하는 이름, 나머지 순이고
+/// 같은 단계 안에서는 편집 거리로 나눔
+pub fn similarity(needle: &str, name: &str) -> f64 {
+    // 한정된 이름(a::b)은 마지막 부분과 비교
+    let name = name.rsplit("::").next().unwrap_or(name).to_lowercase();
+    if needle.is_empty() || name.is_empty() {
+        return 0.0;
+    }
+    if name == needle {
+        return 1.0;
+    }
+
+    let a: Vec<char> = needle.chars().collect();
+    let b: Vec<char> = name.chars().collect();
+    let closeness = 1.0 - levenshtein(&a, &b) as f64 / a.len().max(b.len()) as f64;
+    if name.starts_with(needle) {
+        0.7 + 0.25 * closeness
+    } else if name.contains(needle) {
+        0.4 + 0.25 * closeness
+    } else {
+        0.35 * closeness
+    }
+}
+
+/// 유사도 순으로 정렬하고 limit개만 남김
+pub fn rank(mut symbols: Vec<Symbol>, pattern: &SymbolPattern, limit: usize) -> Vec<Symbol> {
+    let needle = pattern.needle();
+    symbols.sort_by(|a, b| (&a.kind, &a.name, &a.filename, a.line).cmp(&(&b.kind, &b.name, &b.filename, b.line)));
+    symbols.dedup();
+    for symbol in &mut symbols {
+        symbol.score = (similarity(&needle, &symbol.name) * 100.0).round() / 100.0;
+    }
+    symbols.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.name.len().cmp(&b.name.len()))
+            .then_with(|| (&a.name, &a.filename, a.line).cmp(&(&b.name, &b.filename, b.line)))
+    });
+    symbols.truncate(limit);
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(kind: &str, name: &str) -> Symbol {
+        Symbol { kind: kind.to_string(), name: name.to_string(), filename: "lib/a.c".to_string(), line: 1, score: 0.0 }
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        assert_eq!(SymbolPattern::Glob("parse_url?".to_string()).to_regex(), "(?i)parse_url.");
+        assert_eq!(SymbolPattern::Glob("alloc".to_string()).to_regex(), "(?i).*alloc.*");
+        assert_eq!(SymbolPattern::Glob("Curl_*.h".to_string()).to_regex(), "(?i)Curl_.*\\.h");
+        assert_eq!(SymbolPattern::Regex("^Curl_.*".to_string()).to_regex(), "^Curl_.*");
+    }
+
+    #[test]
+    fn test_rank_by_similarity() {
+        let symbols = vec![
+            symbol("function", "Curl_saferealloc"),
+            symbol("function", "alloc"),
+            symbol("macro", "ALLOC_SIZE"),
+            symbol("function", "malloc"),
+            symbol("function", "alloc"),
+            symbol("function", "ns::alloc_buffer"),
+        ];
+        let ranked = rank(symbols, &SymbolPattern::Glob("alloc".to_string()), 4);
+        let names: Vec<_> = ranked.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["alloc", "ALLOC_SIZE", "ns::alloc_buffer", "malloc"]);
+        assert_eq!(ranked[0].score, 1.0);
+        assert!(ranked[1].score > ranked[3].score);
+    }
+}
@@ -0,0 +1,194 @@
+// workspace.rs - 쿼리 실행용 임시 작업 디렉터리
+//
+// 쿼리마다 겹치지 않는 디렉터리를 만들고, 드롭될 때(패닉 포함) 지웁니다.
+// 실패한 쿼리를 디버깅할 때는 query.ql, bqrs, 로그를 남겨 둘 수 있습니다.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::error::{AppError, Result};
+
+// 같은 프로세스 안에서 같은 시각에 만들어도 이름이 겹치지 않게 하는 번호
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 작업 디렉터리를 남겨 둘 조건
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeepArtifacts {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl FromStr for KeepArtifacts {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "never" => Ok(KeepArtifacts::Never),
+            "on-failure" => Ok(KeepArtifacts::OnFailure),
+            "always" => Ok(KeepArtifacts::Always),
+            _ => Err(AppError::InvalidArgument(format!("Unknown keep-artifacts value: {}", s))),
+        }
+    }
+}
+
+/// 작업 디렉터리를 만드는 루트
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    root: PathBuf,
+    keep: KeepArtifacts,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace::new(std::env::temp_dir().join("codeql_agent"))
+    }
+}
+
+impl Workspace {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Workspace {
+            root: root.into(),
+            keep: KeepArtifacts::Never,
+        }
+    }
+
+    pub fn with_keep(mut self, keep: KeepArtifacts) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `<root>/<prefix>_<pid>_<번호>` 디렉터리를 새로 만듦
+    pub fn create(&self, prefix: &str) -> Result<WorkDir> {
+        fs::create_dir_all(&self.root)?;
+        loop {
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            let path = self.root.join(format!("{}_{}_{}", prefix, std::process::id(), id));
+            match fs::create_dir(&path) {
+                Ok(()) => {
+                    return Ok(WorkDir {
+                        path,
+                        keep: self.keep,
+                        succeeded: false,
+                    })
+                }
+                // 이전 실행에서 남겨 둔 디렉터리와 겹치면 다음 번호로
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// 드롭될 때 지워지는 작업 디렉터리
+///
+/// succeed()를 부르지 않고 드롭되면 실패로 봄
+#[derive(Debug)]
+pub struct WorkDir {
+    path: PathBuf,
+    keep: KeepArtifacts,
+    succeeded: bool,
+}
+
+impl WorkDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// 디렉터리 이름 (qlpack 이름 등에 사용)
+    pub fn name(&self) -> String {
+        self.path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+    }
+
+    pub fn succeed(&mut self) {
+        self.succeeded = true;
+    }
+
+    /// 실패했을 때 디렉터리가 남는지 (에러 메시지에 경로를 알려주는 용도)
+    pub fn kept_on_failure(&self) -> bool {
+        self.keep != KeepArtifacts::Never
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let keep = match self.keep {
+            KeepArtifacts::Never => false,
+            KeepArtifacts::OnFailure => !self.succeeded,
+            KeepArtifacts::Always => true,
+        };
+        if !keep {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// 테스트용 임시 디렉터리. assert가 실패해서 패닉이 나도 드롭될 때 지워짐
+#[cfg(test)]
+pub(crate) fn test_dir(prefix: &str) -> WorkDir {
+    Workspace::default().create(prefix).expect("cannot create test directory")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_artifacts_from_str() {
+        assert_eq!("on-failure".parse::<KeepArtifacts>().unwrap(), KeepArtifacts::OnFailure);
+        assert_eq!("ALWAYS".parse::<KeepArtifacts>().unwrap(), KeepArtifacts::Always);
+        assert!(matches!("sometimes".parse::<KeepArtifacts>(), Err(AppError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_unique_dirs_removed_on_drop() {
+        let scratch = test_dir("workspace_unique");
+        let workspace = Workspace::new(scratch.join("root"));
+        let a = workspace.create("query").unwrap();
+        let b = workspace.create("query").unwrap();
+        assert_ne!(a.path(), b.path());
+        fs::write(a.join("query.ql"), "select 1").unwrap();
+
+        let (a_path, b_path) = (a.path().to_path_buf(), b.path().to_path_buf());
+        drop(a);
+        drop(b);
+        assert!(!a_path.exists());
+        assert!(!b_path.exists());
+    }
+
+    #[test]
+    fn test_keep_on_failure() {
+        let scratch = test_dir("workspace_keep");
+        let workspace = Workspace::new(scratch.join("root")).with_keep(KeepArtifacts::OnFailure);
+        let mut succeeded = workspace.create("query").unwrap();
+        succeeded.succeed();
+        let succeeded_path = succeeded.path().to_path_buf();
+        drop(succeeded);
+
+        // 패닉으로 드롭되면 실패로 보고 남김
+        let failed_path = std::panic::catch_unwind(|| {
+            let failed = workspace.create("query").unwrap();
+            let path = failed.path().to_path_buf();
+            fs::write(failed.join("query.ql"), "select 1").unwrap();
+            std::panic::panic_any(path)
+        })
+        .unwrap_err()
+        .downcast::<PathBuf>()
+        .unwrap();
+
+        assert!(!succeeded_path.exists());
+        assert!(failed_path.join("query.ql").exists());
+    }
+}
@@ -1 +1,2 @@
-pub mod utils;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod utils;